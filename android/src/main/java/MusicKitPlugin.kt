@@ -80,4 +80,25 @@ class MusicKitPlugin(private val activity: Activity) : Plugin(activity) {
         }
         invoke.resolve(JSObject().apply { put("token", userToken!!) })
     }
-}
\ No newline at end of file
+
+    // There is no player on Android yet, so the modes cannot be changed
+    @Command
+    fun setShuffleMode(invoke: Invoke) {
+        invoke.reject("Shuffle is not supported on Android", "NOT_SUPPORTED")
+    }
+
+    @Command
+    fun getShuffleMode(invoke: Invoke) {
+        invoke.resolve(JSObject().apply { put("mode", "off") })
+    }
+
+    @Command
+    fun setRepeatMode(invoke: Invoke) {
+        invoke.reject("Repeat is not supported on Android", "NOT_SUPPORTED")
+    }
+
+    @Command
+    fun getRepeatMode(invoke: Invoke) {
+        invoke.resolve(JSObject().apply { put("mode", "none") })
+    }
+}
@@ -13,7 +13,8 @@ const COMMANDS: &[&str] = &[
     "setDeveloperToken",
     "getStorefrontId",
     "getStorefront",
-    "getQueue",
+    "set_storefront",
+    "get_queue",
     "play",
     "pause",
     "stop",
@@ -22,16 +23,36 @@ const COMMANDS: &[&str] = &[
     "previous",
     "skipToItem",
     "setVolume",
-    "setQueue",
-    "updateQueue",
-    "insertTrackAtPosition",
-    "insertTracksAtPosition",
-    "removeTrackFromQueue",
-    "insertTrackNext",
-    "insertTrackLast",
-    "appendTracksToQueue",
+    "set_shuffle_mode",
+    "get_shuffle_mode",
+    "set_repeat_mode",
+    "get_repeat_mode",
+    "set_queue",
+    "update_queue",
+    "insert_track_at_position",
+    "insert_tracks_at_position",
+    "remove_track_from_queue",
+    "insert_track_next",
+    "insert_track_last",
+    "append_tracks_to_queue",
     "getCurrentTrack",
     "getPlaybackState",
+    "remove_track_at_position",
+    "remove_range",
+    "clear_queue",
+    "move_queue_item",
+    "move_queue_items",
+    "undo_queue_change",
+    "redo_queue_change",
+    "search_catalog",
+    "get_search_suggestions",
+    "prefetch_artwork",
+    "evict_artwork",
+    "clear_artwork_cache",
+    "get_album",
+    "get_artist",
+    "get_playlist",
+    "set_queue_from_container",
 ];
 
 fn main() {
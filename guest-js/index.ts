@@ -1,4 +1,4 @@
-import { invoke, addPluginListener } from '@tauri-apps/api/core';
+import { invoke, addPluginListener, convertFileSrc } from '@tauri-apps/api/core';
 import type { PluginListener } from '@tauri-apps/api/core';
 import type {
   MusicKitTrack,
@@ -7,11 +7,115 @@ import type {
   AuthorizationStatusResponse,
   QueueResponse,
   QueueOperationResponse,
-  MusicKitEventMap
+  MusicKitEventMap,
+  CatalogSearchOptions,
+  CatalogSearchResults,
+  DetailOptions,
+  ContainerKind,
+  AlbumDetails,
+  ArtistDetails,
+  PlaylistDetails,
+  SearchType,
+  SearchSuggestions,
+  ShuffleMode,
+  RepeatMode,
+  SuggestionKind,
+  MusicKitError,
+  Artwork,
+  ArtworkFormat
 } from './types';
 
 export * from './types';
 
+/**
+ * Converts a queue operation that resolves with the queue and rejects on failure
+ * into the `{ success, error }` shape queue operations used to return
+ */
+export async function toQueueOperationResponse(
+  operation: Promise<QueueResponse>
+): Promise<QueueOperationResponse> {
+  try {
+    return { success: true, queue: await operation };
+  } catch (error) {
+    const message = (error as MusicKitError)?.message ?? String(error);
+    return { success: false, error: message };
+  }
+}
+
+/** The largest edge Apple serves artwork at when the artwork does not report its own size */
+export const MAX_ARTWORK_SIZE = 3000;
+
+/**
+ * The URL of the artwork rendered at `width` x `height` pixels in `format`.
+ * URLs without placeholders are returned unchanged.
+ */
+export function artworkUrl(
+  artwork: Artwork,
+  width: number,
+  height: number,
+  format: ArtworkFormat = 'jpg'
+): string {
+  const isTemplate = artwork.url.includes('{w}') || artwork.url.includes('{h}');
+  const url = artwork.url
+    .replaceAll('{w}', String(width))
+    .replaceAll('{h}', String(height))
+    .replaceAll('{c}', 'bb');
+  if (url.includes('{f}')) {
+    return url.replaceAll('{f}', format);
+  }
+  if (!isTemplate) {
+    return url;
+  }
+  return url.replace(/\.(jpe?g|png|webp)$/i, `.${format}`);
+}
+
+/**
+ * The pixel size to request for a `width` x `height` box in CSS pixels, scaled down
+ * to the largest size the artwork is available in
+ */
+export function artworkSize(
+  artwork: Artwork,
+  width: number,
+  height: number,
+  devicePixelRatio: number = window.devicePixelRatio
+): [number, number] {
+  const ratio = Number.isFinite(devicePixelRatio) && devicePixelRatio > 0 ? devicePixelRatio : 1;
+  const pixelWidth = Math.ceil(Math.max(width, 1) * ratio);
+  const pixelHeight = Math.ceil(Math.max(height, 1) * ratio);
+  const maxWidth = Math.max(artwork.width ?? MAX_ARTWORK_SIZE, 1);
+  const maxHeight = Math.max(artwork.height ?? MAX_ARTWORK_SIZE, 1);
+  const scale = Math.min(maxWidth / pixelWidth, maxHeight / pixelHeight, 1);
+  return [
+    Math.max(Math.round(pixelWidth * scale), 1),
+    Math.max(Math.round(pixelHeight * scale), 1)
+  ];
+}
+
+/**
+ * The URL for a `width` x `height` box in CSS pixels on this display
+ */
+export function artworkUrlFor(
+  artwork: Artwork,
+  width: number,
+  height: number,
+  format: ArtworkFormat = 'jpg',
+  devicePixelRatio: number = window.devicePixelRatio
+): string {
+  const [pixelWidth, pixelHeight] = artworkSize(artwork, width, height, devicePixelRatio);
+  return artworkUrl(artwork, pixelWidth, pixelHeight, format);
+}
+
+/** The URI scheme the plugin serves cached artwork through */
+export const ARTWORK_SCHEME = 'musickit-artwork';
+
+/**
+ * The URL that loads the image at `url` through the plugin's artwork cache
+ * (requires the `api` feature and the `artworkCache` option)
+ */
+export function cachedArtworkUrl(url: string): string {
+  return convertFileSrc(url, ARTWORK_SCHEME);
+}
+
 export class MusicKit {
   private eventListeners: Map<string, PluginListener[]> = new Map();
 
@@ -78,11 +182,18 @@ export class MusicKit {
     return await invoke('plugin:musickit|getStorefrontId');
   }
 
+  /**
+   * Override the storefront used for catalog requests
+   */
+  async setStorefront(storefront: string): Promise<void> {
+    await invoke('plugin:musickit|set_storefront', { storefront });
+  }
+
   /**
    * Get current queue
    */
   async getQueue(): Promise<QueueResponse> {
-    return await invoke('plugin:musickit|getQueue');
+    return await invoke('plugin:musickit|get_queue');
   }
 
   /**
@@ -141,60 +252,251 @@ export class MusicKit {
     await invoke('plugin:musickit|setVolume', { volume });
   }
 
+  /**
+   * Set the shuffle mode
+   */
+  async setShuffleMode(mode: ShuffleMode): Promise<void> {
+    await invoke('plugin:musickit|set_shuffle_mode', { mode });
+  }
+
+  /**
+   * Get the shuffle mode
+   */
+  async getShuffleMode(): Promise<ShuffleMode> {
+    return await invoke('plugin:musickit|get_shuffle_mode');
+  }
+
+  /**
+   * Set the repeat mode
+   */
+  async setRepeatMode(mode: RepeatMode): Promise<void> {
+    await invoke('plugin:musickit|set_repeat_mode', { mode });
+  }
+
+  /**
+   * Get the repeat mode
+   */
+  async getRepeatMode(): Promise<RepeatMode> {
+    return await invoke('plugin:musickit|get_repeat_mode');
+  }
+
   /**
    * Set the playback queue
    */
-  async setQueue(tracks: MusicKitTrack[], startPlaying: boolean = false, startPosition: number = 0): Promise<QueueOperationResponse> {
-    return await invoke('plugin:musickit|setQueue', { tracks, startPlaying, startPosition });
+  async setQueue(tracks: MusicKitTrack[], startPlaying: boolean = false, startPosition: number = 0): Promise<QueueResponse> {
+    return await invoke('plugin:musickit|set_queue', { tracks, startPlaying, startPosition });
   }
 
   /**
    * Update queue
    */
-  async updateQueue(tracks: MusicKitTrack[]): Promise<QueueOperationResponse> {
-    return await invoke('plugin:musickit|updateQueue', { tracks });
+  async updateQueue(tracks: MusicKitTrack[]): Promise<QueueResponse> {
+    return await invoke('plugin:musickit|update_queue', { tracks });
   }
 
   /**
    * Insert track at position
    */
-  async insertTrackAtPosition(track: MusicKitTrack, position: number): Promise<QueueOperationResponse> {
-    return await invoke('plugin:musickit|insertTrackAtPosition', { track, position });
+  async insertTrackAtPosition(track: MusicKitTrack, position: number): Promise<QueueResponse> {
+    return await invoke('plugin:musickit|insert_track_at_position', { track, position });
   }
 
   /**
    * Insert tracks at position
    */
-  async insertTracksAtPosition(tracks: MusicKitTrack[], position: number): Promise<QueueOperationResponse> {
-    return await invoke('plugin:musickit|insertTracksAtPosition', { tracks, position });
+  async insertTracksAtPosition(tracks: MusicKitTrack[], position: number): Promise<QueueResponse> {
+    return await invoke('plugin:musickit|insert_tracks_at_position', { tracks, position });
   }
 
   /**
    * Remove track from queue
    */
-  async removeTrackFromQueue(trackId: string): Promise<QueueOperationResponse> {
-    return await invoke('plugin:musickit|removeTrackFromQueue', { trackId });
+  async removeTrackFromQueue(trackId: string): Promise<QueueResponse> {
+    return await invoke('plugin:musickit|remove_track_from_queue', { trackId });
   }
 
   /**
    * Insert track next
    */
-  async insertTrackNext(track: MusicKitTrack): Promise<QueueOperationResponse> {
-    return await invoke('plugin:musickit|insertTrackNext', { track });
+  async insertTrackNext(track: MusicKitTrack): Promise<QueueResponse> {
+    return await invoke('plugin:musickit|insert_track_next', { track });
   }
 
   /**
    * Insert track last
    */
-  async insertTrackLast(track: MusicKitTrack): Promise<QueueOperationResponse> {
-    return await invoke('plugin:musickit|insertTrackLast', { track });
+  async insertTrackLast(track: MusicKitTrack): Promise<QueueResponse> {
+    return await invoke('plugin:musickit|insert_track_last', { track });
   }
 
   /**
    * Append tracks to queue
    */
-  async appendTracksToQueue(tracks: MusicKitTrack[]): Promise<QueueOperationResponse> {
-    return await invoke('plugin:musickit|appendTracksToQueue', { tracks });
+  async appendTracksToQueue(tracks: MusicKitTrack[]): Promise<QueueResponse> {
+    return await invoke('plugin:musickit|append_tracks_to_queue', { tracks });
+  }
+
+  /**
+   * Remove the queue item at a position
+   */
+  async removeTrackAtPosition(position: number): Promise<QueueResponse> {
+    return await invoke('plugin:musickit|remove_track_at_position', { position });
+  }
+
+  /**
+   * Remove the queue items from `start` up to, but not including, `end`
+   */
+  async removeRange(start: number, end: number): Promise<QueueResponse> {
+    return await invoke('plugin:musickit|remove_range', { start, end });
+  }
+
+  /**
+   * Remove every item from the queue
+   */
+  async clearQueue(): Promise<QueueResponse> {
+    return await invoke('plugin:musickit|clear_queue');
+  }
+
+  /**
+   * Move the item at `from` to index `to`, keeping the current item playing
+   */
+  async moveQueueItem(from: number, to: number): Promise<QueueResponse> {
+    return await invoke('plugin:musickit|move_queue_item', { from, to });
+  }
+
+  /**
+   * Move several items into one block starting at `to`, counted in the queue
+   * without the moved items
+   */
+  async moveQueueItems(indices: number[], to: number): Promise<QueueResponse> {
+    return await invoke('plugin:musickit|move_queue_items', { indices, to });
+  }
+
+  /**
+   * Undo the last queue change, restoring the previous queue and position
+   */
+  async undoQueueChange(): Promise<QueueResponse> {
+    return await invoke('plugin:musickit|undo_queue_change');
+  }
+
+  /**
+   * Redo the last undone queue change
+   */
+  async redoQueueChange(): Promise<QueueResponse> {
+    return await invoke('plugin:musickit|redo_queue_change');
+  }
+
+  /**
+   * Search the Apple Music catalog (requires the `api` feature)
+   */
+  async searchCatalog(term: string, options: CatalogSearchOptions = {}): Promise<CatalogSearchResults> {
+    return await invoke('plugin:musickit|search_catalog', {
+      term,
+      types: options.types ?? [],
+      limit: options.limit ?? null,
+      offset: options.offset ?? null,
+      storefront: options.storefront ?? null
+    });
+  }
+
+  /**
+   * Get type-ahead suggestions for a partial search term (requires the `api` feature).
+   * Resolves to null when a newer call superseded this one.
+   */
+  async getSearchSuggestions(
+    term: string,
+    kinds: SuggestionKind[] = ['terms', 'topResults'],
+    types?: SearchType[],
+    limit?: number
+  ): Promise<SearchSuggestions | null> {
+    return await invoke('plugin:musickit|get_search_suggestions', {
+      term,
+      kinds,
+      types: types ?? null,
+      limit: limit ?? null
+    });
+  }
+
+  /**
+   * Download images into the artwork cache ahead of time (requires the `api` feature)
+   */
+  async prefetchArtwork(urls: string[]): Promise<void> {
+    await invoke('plugin:musickit|prefetch_artwork', { urls });
+  }
+
+  /**
+   * Remove an image from the artwork cache, resolving to whether it was cached
+   */
+  async evictArtwork(url: string): Promise<boolean> {
+    return await invoke('plugin:musickit|evict_artwork', { url });
+  }
+
+  /**
+   * Remove every image from the artwork cache
+   */
+  async clearArtworkCache(): Promise<void> {
+    await invoke('plugin:musickit|clear_artwork_cache');
+  }
+
+  /**
+   * Get an album with every page of its tracks (requires the `api` feature)
+   */
+  async getAlbum(id: string, options: DetailOptions = {}): Promise<AlbumDetails> {
+    return await invoke('plugin:musickit|get_album', {
+      id,
+      include: options.include ?? null,
+      extend: options.extend ?? null,
+      storefront: options.storefront ?? null,
+      library: options.library ?? null,
+      maxItems: options.maxItems ?? null
+    });
+  }
+
+  /**
+   * Get an artist with their albums and top songs (requires the `api` feature)
+   */
+  async getArtist(id: string, options: DetailOptions = {}): Promise<ArtistDetails> {
+    return await invoke('plugin:musickit|get_artist', {
+      id,
+      include: options.include ?? null,
+      extend: options.extend ?? null,
+      storefront: options.storefront ?? null,
+      library: options.library ?? null,
+      maxItems: options.maxItems ?? null
+    });
+  }
+
+  /**
+   * Get a playlist with every page of its tracks (requires the `api` feature)
+   */
+  async getPlaylist(id: string, options: DetailOptions = {}): Promise<PlaylistDetails> {
+    return await invoke('plugin:musickit|get_playlist', {
+      id,
+      include: options.include ?? null,
+      extend: options.extend ?? null,
+      storefront: options.storefront ?? null,
+      library: options.library ?? null,
+      maxItems: options.maxItems ?? null
+    });
+  }
+
+  /**
+   * Replace the queue with a whole catalog album, playlist, artist's top songs or
+   * station and start playing it (requires the `api` feature). `startIndex` indexes
+   * the container's own track order
+   */
+  async setQueueFromContainer(
+    kind: ContainerKind,
+    id: string,
+    startIndex = 0,
+    shuffle = false
+  ): Promise<QueueResponse> {
+    return await invoke('plugin:musickit|set_queue_from_container', {
+      kind,
+      id,
+      startIndex,
+      shuffle
+    });
   }
 
   /**
@@ -1,16 +1,60 @@
 export interface MusicKitTrack {
+  /** The identifier the player plays the track with */
   id: string;
+  catalogId?: string;
+  libraryId?: string;
   title: string;
   artist: string;
   album: string;
+  genres: string[];
+  /** The duration in seconds */
   duration: number;
-  artworkUrl?: string;
+  artwork?: Artwork | null;
   isExplicit: boolean;
   isPlayable: boolean;
+  contentRating?: string;
+  isrc?: string;
+  trackNumber?: number;
+  discNumber?: number;
+  releaseDate?: string;
+  composer?: string;
+  /** Fields the plugin does not model, passed through unchanged */
+  [extra: string]: unknown;
 }
 
+/**
+ * The track shape state updates used to carry, replaced by `MusicKitTrack`
+ */
+export interface MusicKitTrackData {
+  id: string;
+  title: string;
+  artistName: string;
+  albumName: string;
+  genreNames: string;
+  artwork: string;
+  durationInMillis: number;
+}
+
+/**
+ * Mirrors the Rust `AuthorizationStatus` enum. Values the native side reports
+ * that are not listed here are passed through unchanged.
+ */
+export type AuthorizationStatus =
+  | 'authorized'
+  | 'notAuthorized'
+  | 'denied'
+  | 'restricted'
+  | 'notDetermined'
+  | 'notInitialized'
+  | 'error'
+  | (string & {});
+
+export type ShuffleMode = 'off' | 'songs' | 'albums' | (string & {});
+
+export type RepeatMode = 'none' | 'one' | 'all' | (string & {});
+
 export interface AuthorizationResponse {
-  status: 'authorized' | 'notAuthorized' | 'error';
+  status: AuthorizationStatus;
   error?: string;
 }
 
@@ -20,7 +64,7 @@ export interface UnauthorizeResponse {
 }
 
 export interface AuthorizationStatusResponse {
-  status: 'authorized' | 'notAuthorized' | 'notInitialized';
+  status: AuthorizationStatus;
 }
 
 export interface QueueResponse {
@@ -31,6 +75,8 @@ export interface QueueResponse {
 export interface QueueOperationResponse {
   success: boolean;
   error?: string;
+  /** The queue after the operation, when it succeeded */
+  queue?: QueueResponse;
 }
 
 export interface QueueUpdateEvent {
@@ -38,24 +84,22 @@ export interface QueueUpdateEvent {
   position: number;
 }
 
+export interface QueueRestoredEvent {
+  items: MusicKitTrack[];
+  position: number;
+  currentTime: number;
+}
+
 export interface PlaybackState {
   playing: boolean;
   paused: boolean;
-  currentTrack: {
-    id: string;
-    title: string;
-    artistName: string;
-    albumName: string;
-    genreNames: string;
-    durationInMillis: number;
-    artwork: string;
-  } | null;
+  currentTrack: MusicKitTrack | null;
   currentTime: number;
   duration: number;
   progress: number;
   queuePosition: number;
-  shuffleMode: 'on' | 'off';
-  repeatMode: 'none' | 'all';
+  shuffleMode: ShuffleMode;
+  repeatMode: RepeatMode;
   volume: number;
 }
 
@@ -67,6 +111,27 @@ export interface PlaybackTimeEvent {
   currentTime: number;
 }
 
+export type MusicKitErrorCode =
+  | 'NOT_INITIALIZED'
+  | 'NOT_AUTHORIZED'
+  | 'SUBSCRIPTION_REQUIRED'
+  | 'CONTENT_UNAVAILABLE'
+  | 'NETWORK'
+  | 'RATE_LIMITED'
+  | 'INVALID_ARGUMENT'
+  | 'INVALID_STATE'
+  | 'NOT_SUPPORTED'
+  | 'CONFIGURATION'
+  | 'INTERNAL'
+  | 'UNKNOWN';
+
+/** The shape every rejected command rejects with */
+export interface MusicKitError {
+  code: MusicKitErrorCode;
+  message: string;
+  details?: Record<string, unknown>;
+}
+
 export interface ErrorEvent {
   error: string;
   code?: string;
@@ -76,9 +141,204 @@ export interface QueueChangeEvent {
   success: boolean;
 }
 
+export interface PlayerTrackChangeEvent {
+  track: MusicKitTrack;
+}
+
+/** Player events emitted on the app by the Rust event layer */
+export interface PlayerAdapterEventMap {
+  'PLAYER_ADAPTER_EVENTS.INITIALIZED': null;
+  'PLAYER_ADAPTER_EVENTS.AUTHORIZATION_STATUS_CHANGE': AuthorizationStatusResponse;
+  'PLAYER_ADAPTER_EVENTS.STATE_UPDATE': StateUpdateEvent;
+  'PLAYER_ADAPTER_EVENTS.TRACK_CHANGE': PlayerTrackChangeEvent;
+  'PLAYER_ADAPTER_EVENTS.QUEUE_UPDATE': QueueUpdateEvent;
+  'PLAYER_ADAPTER_EVENTS.QUEUE_RESTORED': QueueRestoredEvent;
+  'PLAYER_ADAPTER_EVENTS.ERROR': ErrorEvent;
+}
+
 export interface MusicKitEventMap {
   'musickit-playback-state-changed': StateUpdateEvent;
   'musickit-track-changed': TrackChangeEvent;
   'musickit-playback-time-changed': PlaybackTimeEvent;
   'musickit-queue-changed': QueueChangeEvent;
-} 
\ No newline at end of file
+}
+
+export type ArtworkFormat = 'jpg' | 'png' | 'webp';
+
+/**
+ * Artwork with `{w}` and `{h}` placeholders in `url`; resolve it with
+ * `artworkUrl` or `artworkUrlFor`. Colors are hex strings without a leading `#`.
+ */
+export interface Artwork {
+  url: string;
+  /** The largest width the artwork is available in */
+  width?: number | null;
+  /** The largest height the artwork is available in */
+  height?: number | null;
+  bgColor?: string;
+  textColor1?: string;
+  textColor2?: string;
+  textColor3?: string;
+  textColor4?: string;
+}
+
+/** @deprecated Use `Artwork` */
+export type ArtworkAttributes = Artwork;
+
+export interface PlayParameters {
+  id: string;
+  kind: string;
+  catalogId?: string;
+  isLibrary?: boolean;
+}
+
+export interface Resource<A> {
+  id: string;
+  type: string;
+  href?: string;
+  attributes?: A;
+  relationships?: Record<string, unknown>;
+}
+
+export interface Page<T> {
+  data: T[];
+  next?: string;
+  meta?: Record<string, unknown>;
+}
+
+export interface SongAttributes {
+  name: string;
+  artistName: string;
+  albumName?: string;
+  genreNames: string[];
+  durationInMillis?: number;
+  artwork?: Artwork;
+  contentRating?: string;
+  isrc?: string;
+  trackNumber?: number;
+  discNumber?: number;
+  releaseDate?: string;
+  composerName?: string;
+  url?: string;
+  playParams?: PlayParameters;
+}
+
+export interface AlbumAttributes {
+  name: string;
+  artistName: string;
+  genreNames: string[];
+  artwork?: Artwork;
+  contentRating?: string;
+  releaseDate?: string;
+  trackCount?: number;
+  recordLabel?: string;
+  copyright?: string;
+  upc?: string;
+  isSingle: boolean;
+  isComplete: boolean;
+  url?: string;
+  playParams?: PlayParameters;
+  /** Extended attributes requested with `extend` */
+  [extra: string]: unknown;
+}
+
+export interface ArtistAttributes {
+  name: string;
+  genreNames: string[];
+  artwork?: Artwork;
+  url?: string;
+  /** Extended attributes requested with `extend` */
+  [extra: string]: unknown;
+}
+
+export interface PlaylistAttributes {
+  name: string;
+  curatorName?: string;
+  playlistType?: string;
+  artwork?: Artwork;
+  lastModifiedDate?: string;
+  url?: string;
+  playParams?: PlayParameters;
+  /** Extended attributes requested with `extend` */
+  [extra: string]: unknown;
+}
+
+export interface StationAttributes {
+  name: string;
+  artwork?: Artwork;
+  isLive: boolean;
+  durationInMillis?: number;
+  url?: string;
+  playParams?: PlayParameters;
+}
+
+export type Song = Resource<SongAttributes>;
+export type Album = Resource<AlbumAttributes>;
+export type Artist = Resource<ArtistAttributes>;
+export type Playlist = Resource<PlaylistAttributes>;
+export type Station = Resource<StationAttributes>;
+
+export type SearchType = 'songs' | 'albums' | 'artists' | 'playlists' | 'stations';
+
+export interface CatalogSearchOptions {
+  types?: SearchType[];
+  limit?: number;
+  offset?: number;
+  storefront?: string;
+}
+
+export interface CatalogSearchResults {
+  songs?: Page<Song>;
+  albums?: Page<Album>;
+  artists?: Page<Artist>;
+  playlists?: Page<Playlist>;
+  stations?: Page<Station>;
+}
+
+/** The kind of catalog container a queue can be built from; `artist` queues the artist's top songs */
+export type ContainerKind = 'album' | 'playlist' | 'artist' | 'station';
+
+export interface DetailOptions {
+  /** Relationships to include beyond the ones the details always carry, e.g. `artists` */
+  include?: string[];
+  /** Extended attributes to request, e.g. `editorialVideo` */
+  extend?: string[];
+  storefront?: string;
+  /** Fetch the resource from the user's library instead of the catalog */
+  library?: boolean;
+  /** The most items to load per relationship; every page is loaded when unset */
+  maxItems?: number;
+}
+
+export interface AlbumDetails {
+  album: Album;
+  tracks: MusicKitTrack[];
+  artists: Artist[];
+}
+
+export interface ArtistDetails {
+  artist: Artist;
+  albums: Album[];
+  playlists: Playlist[];
+  /** Empty for library artists */
+  topSongs: MusicKitTrack[];
+}
+
+export interface PlaylistDetails {
+  playlist: Playlist;
+  tracks: MusicKitTrack[];
+}
+
+export type SuggestionKind = 'terms' | 'topResults';
+
+export interface TermSuggestion {
+  searchTerm: string;
+  displayTerm: string;
+}
+
+export type TopResult = Song | Album | Artist | Playlist | Station;
+
+export interface SearchSuggestions {
+  terms: TermSuggestion[];
+  topResults: TopResult[];
+}
@@ -27,8 +27,8 @@ struct SetVolumeArgs: Decodable {
     let volume: Double
 }
 
-struct RemoveTrackAtPositionArgs: Decodable {
-    let position: Int
+struct SetModeArgs: Decodable {
+    let mode: String
 }
 
 struct SetQueueArgs: Decodable {
@@ -37,72 +37,182 @@ struct SetQueueArgs: Decodable {
     let startPosition: Int
 }
 
-struct UpdateQueueArgs: Decodable {
+struct RearrangeQueueArgs: Decodable {
     let tracks: [FullTrackData]
-}
-
-struct InsertTrackArgs: Decodable {
-    let track: FullTrackData
     let position: Int
 }
 
-struct InsertTracksArgs: Decodable {
+struct SetQueueFromContainerArgs: Decodable {
+    let kind: String
+    let id: String
     let tracks: [FullTrackData]
-    let position: Int
+    let startIndex: Int
+    let shuffle: Bool
 }
 
-struct RemoveTrackArgs: Decodable {
-    let trackId: String
-}
+struct ArtworkData: Codable {
+    let url: String
+    let width: Int?
+    let height: Int?
+    let bgColor: String?
+    let textColor1: String?
+    let textColor2: String?
+    let textColor3: String?
+    let textColor4: String?
 
-struct AppendTracksArgs: Decodable {
-    let tracks: [FullTrackData]
+    init(url: String) {
+        self.url = url
+        width = nil
+        height = nil
+        bgColor = nil
+        textColor1 = nil
+        textColor2 = nil
+        textColor3 = nil
+        textColor4 = nil
+    }
+
+    var dictionary: [String: Any] {
+        var result: [String: Any] = ["url": url]
+        let optional: [String: Any?] = [
+            "width": width,
+            "height": height,
+            "bgColor": bgColor,
+            "textColor1": textColor1,
+            "textColor2": textColor2,
+            "textColor3": textColor3,
+            "textColor4": textColor4
+        ]
+        for (key, value) in optional {
+            if let value = value {
+                result[key] = value
+            }
+        }
+        return result
+    }
 }
 
 struct FullTrackData: Codable {
     let id: String
+    let catalogId: String?
+    let libraryId: String?
     let title: String
-    let artistName: String
-    let albumName: String
-    let genreNames: String
-    let artwork: String
-    let durationInMillis: Int
+    let artist: String
+    let album: String
+    let genres: [String]
+    let artwork: ArtworkData?
+    let duration: Double
     let isExplicit: Bool
     let isPlayable: Bool
-}
+    let contentRating: String?
+    let isrc: String?
+    let trackNumber: Int?
+    let discNumber: Int?
+    let releaseDate: String?
+    let composer: String?
 
-// Queue Manager for better queue management
-class QueueManager {
-    static let shared = QueueManager()
-    
-    private var queue: [FullTrackData] = [] {
-        didSet {
-            queuePublisher.send(queue)
-        }
+    private enum CodingKeys: String, CodingKey {
+        case id, catalogId, libraryId, title, artist, album, genres, artwork, duration
+        case isExplicit, isPlayable, contentRating, isrc, trackNumber, discNumber, releaseDate, composer
     }
-    
-    private var queuePublisher = PassthroughSubject<[FullTrackData], Never>()
-    
-    private init() {}
-    
-    func updateQueue(with tracks: [FullTrackData]) {
-        queue = tracks
+
+    // Keys of the track shape used before tracks were unified
+    private enum LegacyKeys: String, CodingKey {
+        case artistName, albumName, genreNames, artworkUrl, durationInMillis
     }
-    
-    func findTrack(byId id: String) -> FullTrackData? {
-        return queue.first { $0.id == id }
+
+    init(from decoder: Decoder) throws {
+        let container = try decoder.container(keyedBy: CodingKeys.self)
+        let legacy = try decoder.container(keyedBy: LegacyKeys.self)
+        id = try container.decode(String.self, forKey: .id)
+        catalogId = try container.decodeIfPresent(String.self, forKey: .catalogId)
+        libraryId = try container.decodeIfPresent(String.self, forKey: .libraryId)
+        title = try container.decodeIfPresent(String.self, forKey: .title) ?? ""
+        artist = try container.decodeIfPresent(String.self, forKey: .artist)
+            ?? legacy.decodeIfPresent(String.self, forKey: .artistName) ?? ""
+        album = try container.decodeIfPresent(String.self, forKey: .album)
+            ?? legacy.decodeIfPresent(String.self, forKey: .albumName) ?? ""
+        if let genres = try container.decodeIfPresent([String].self, forKey: .genres) {
+            self.genres = genres
+        } else {
+            genres = FullTrackData.splitGenres(try legacy.decodeIfPresent(String.self, forKey: .genreNames))
+        }
+        // Artwork is either an object, a bare URL or an `artworkUrl` string
+        let artwork = (try? container.decodeIfPresent(ArtworkData.self, forKey: .artwork))
+            ?? (try? container.decodeIfPresent(String.self, forKey: .artwork)).map(ArtworkData.init(url:))
+            ?? (try legacy.decodeIfPresent(String.self, forKey: .artworkUrl)).map(ArtworkData.init(url:))
+        self.artwork = artwork?.url.isEmpty == false ? artwork : nil
+        if let duration = try container.decodeIfPresent(Double.self, forKey: .duration) {
+            self.duration = duration
+        } else {
+            duration = Double(try legacy.decodeIfPresent(Int.self, forKey: .durationInMillis) ?? 0) / 1000.0
+        }
+        contentRating = try container.decodeIfPresent(String.self, forKey: .contentRating)
+        isExplicit = try container.decodeIfPresent(Bool.self, forKey: .isExplicit) ?? (contentRating == "explicit")
+        isPlayable = try container.decodeIfPresent(Bool.self, forKey: .isPlayable) ?? true
+        isrc = try container.decodeIfPresent(String.self, forKey: .isrc)
+        trackNumber = try container.decodeIfPresent(Int.self, forKey: .trackNumber)
+        discNumber = try container.decodeIfPresent(Int.self, forKey: .discNumber)
+        releaseDate = try container.decodeIfPresent(String.self, forKey: .releaseDate)
+        composer = try container.decodeIfPresent(String.self, forKey: .composer)
     }
-    
-    func clearQueue() {
-        queue.removeAll()
+
+    // Metadata for an item that is not in the shadow queue
+    init(item: MPMediaItem) {
+        id = item.playbackStoreID
+        catalogId = nil
+        libraryId = nil
+        title = item.title ?? ""
+        artist = item.artist ?? ""
+        album = item.albumTitle ?? ""
+        genres = FullTrackData.splitGenres(item.genre)
+        artwork = nil
+        duration = item.playbackDuration
+        isExplicit = item.isExplicitItem
+        isPlayable = true
+        contentRating = nil
+        isrc = nil
+        trackNumber = item.albumTrackNumber > 0 ? item.albumTrackNumber : nil
+        discNumber = item.discNumber > 0 ? item.discNumber : nil
+        releaseDate = nil
+        composer = item.composer
     }
-    
-    func getQueue() -> [FullTrackData] {
-        return queue
+
+    private static func splitGenres(_ genres: String?) -> [String] {
+        return (genres ?? "")
+            .split(separator: ",")
+            .map { $0.trimmingCharacters(in: .whitespaces) }
+            .filter { !$0.isEmpty }
     }
-    
-    var publisher: AnyPublisher<[FullTrackData], Never> {
-        return queuePublisher.eraseToAnyPublisher()
+
+    // The track in the shape the Rust side deserializes
+    var dictionary: [String: Any] {
+        var result: [String: Any] = [
+            "id": id,
+            "title": title,
+            "artist": artist,
+            "album": album,
+            "genres": genres,
+            "duration": duration,
+            "isExplicit": isExplicit,
+            "isPlayable": isPlayable
+        ]
+        let optional: [String: Any?] = [
+            "catalogId": catalogId,
+            "libraryId": libraryId,
+            "artwork": artwork?.dictionary,
+            "contentRating": contentRating,
+            "isrc": isrc,
+            "trackNumber": trackNumber,
+            "discNumber": discNumber,
+            "releaseDate": releaseDate,
+            "composer": composer
+        ]
+        for (key, value) in optional {
+            if let value = value {
+                result[key] = value
+            }
+        }
+        return result
     }
 }
 
@@ -114,7 +224,7 @@ func init_plugin_musickit() -> MusicKitPlugin {
 
 @available(iOS 15.0, *)
 public class MusicKitPlugin: Plugin {
-    public let player = MPMusicPlayerController.applicationMusicPlayer
+    public let player = MPMusicPlayerController.applicationQueuePlayer
     public var userToken: String? = nil
     public var developerToken: String? = nil
     private var timeObserver: Timer?
@@ -122,8 +232,8 @@ public class MusicKitPlugin: Plugin {
     private var debounceTimer: Timer?
     private var playbackStateDebounceTimer: Timer?
     
-    // Queue management
-    private let queueManager = QueueManager.shared
+    // The queue last applied by the Rust side, kept for track metadata
+    private var queuedTracks: [FullTrackData] = []
     
     override public init() {
         super.init()
@@ -147,12 +257,11 @@ public class MusicKitPlugin: Plugin {
     }
     
     @objc private func appDidBecomeActive() {
-        // Sync queue if player queue is empty but we have tracks in our queue manager
+        // Sync queue if player queue is empty but we have tracks from the Rust side
         if player.nowPlayingItem == nil {
-            let queue = queueManager.getQueue()
-            if !queue.isEmpty {
+            if !queuedTracks.isEmpty {
                 print("MusicKit Plugin: Queue is empty, syncing with local queue copy.")
-                let storeIDs = queue.map { $0.id }
+                let storeIDs = queuedTracks.map { $0.id }
                 let queueDescriptor = MPMusicPlayerStoreQueueDescriptor(storeIDs: storeIDs)
                 player.setQueue(with: queueDescriptor)
                 player.prepareToPlay()
@@ -196,12 +305,12 @@ public class MusicKitPlugin: Plugin {
                         print("MusicKit Plugin: User token stored successfully")
                     } else {
                         print("MusicKit Plugin: Developer token not set, rejecting...")
-                        invoke.reject("Developer token not set.")
+                        invoke.reject("Developer token not set.", code: "NOT_AUTHORIZED")
                         return
                     }
                 } catch {
                     print("MusicKit Plugin: Error fetching music user token: \(error.localizedDescription)")
-                    invoke.reject("Failed to fetch music user token: \(error.localizedDescription)")
+                    invoke.reject("Failed to fetch music user token: \(error.localizedDescription)", code: errorCode(for: error))
                     return
                 }
             } else {
@@ -240,7 +349,7 @@ public class MusicKitPlugin: Plugin {
             invoke.resolve()
         } catch {
             print("MusicKit Plugin: Error in setUserToken: \(error.localizedDescription)")
-            invoke.reject("Failed to parse arguments: \(error.localizedDescription)")
+            invoke.reject("Failed to parse arguments: \(error.localizedDescription)", code: "INVALID_ARGUMENT")
         }
     }
 
@@ -269,7 +378,7 @@ public class MusicKitPlugin: Plugin {
         print("MusicKit Plugin: developer token set.")
         invoke.resolve()
     } catch {
-        invoke.reject("Failed to parse arguments: \(error.localizedDescription)")
+        invoke.reject("Failed to parse arguments: \(error.localizedDescription)", code: "INVALID_ARGUMENT")
     }
   }
   
@@ -285,8 +394,13 @@ public class MusicKitPlugin: Plugin {
   
   @objc public func getAuthorizationStatus(_ invoke: Invoke) {
     print("MusicKit Plugin: getAuthorizationStatus called")
-    let status = self.userToken != nil ? "authorized" : "notAuthorized"
-    invoke.resolve(["status": status])
+    let status = MusicAuthorization.currentStatus
+    // Access granted without a user token yet still needs `authorize` to fetch one.
+    if status == .authorized && self.userToken == nil {
+      invoke.resolve(["status": "notAuthorized"])
+      return
+    }
+    invoke.resolve(["status": status.toString()])
   }
   
   @objc public func setStorefront(_ invoke: Invoke) {
@@ -297,26 +411,14 @@ public class MusicKitPlugin: Plugin {
   @objc public func getQueue(_ invoke: Invoke) {
     print("MusicKit Plugin: getQueue called")
     
-    let queue = queueManager.getQueue()
-    let currentTrackIndex = player.indexOfNowPlayingItem
+    let currentTrackIndex = nowPlayingPosition()
     
     let result: [String: Any] = [
-      "items": queue.map { track in
-        [
-          "id": track.id,
-          "title": track.title,
-          "artist": track.artistName,
-          "album": track.albumName,
-          "duration": Double(track.durationInMillis) / 1000.0,
-          "artworkUrl": track.artwork,
-          "isExplicit": track.isExplicit,
-          "isPlayable": track.isPlayable
-        ]
-      },
+      "items": queuedTracks.map { $0.dictionary },
       "position": currentTrackIndex
     ]
     
-    print("MusicKit Plugin: getQueue returning \(queue.count) tracks, position: \(currentTrackIndex)")
+    print("MusicKit Plugin: getQueue returning \(queuedTracks.count) tracks, position: \(currentTrackIndex)")
     invoke.resolve(result)
   }
   
@@ -352,7 +454,7 @@ public class MusicKitPlugin: Plugin {
         invoke.resolve() // RESOLVE IMMEDIATELY FIRST
         player.currentPlaybackTime = time // THEN execute action
     } catch {
-        invoke.reject("Invalid arguments for seek: \(error.localizedDescription)")
+        invoke.reject("Invalid arguments for seek: \(error.localizedDescription)", code: "INVALID_ARGUMENT")
     }
   }
   
@@ -378,11 +480,9 @@ public class MusicKitPlugin: Plugin {
         let startPlaying = args.startPlaying
         print("MusicKit Plugin: Skipping to track: \(trackId), startPlaying: \(startPlaying)")
         
-        // Find the track in our queue manager
-        if queueManager.findTrack(byId: trackId) != nil {
-            // Find the position of this track in the current queue
-            let queue = queueManager.getQueue()
-            if let position = queue.firstIndex(where: { $0.id == trackId }) {
+        // Find the track in the applied queue
+        if findTrack(byId: trackId) != nil {
+            if let position = queuedTracks.firstIndex(where: { $0.id == trackId }) {
                 print("MusicKit Plugin: Successfully skipped to track at position: \(position)")
                 invoke.resolve() // RESOLVE IMMEDIATELY FIRST
                 
@@ -394,13 +494,13 @@ public class MusicKitPlugin: Plugin {
                     player.play()
                 }
             } else {
-                invoke.reject("Track not found in queue")
+                invoke.reject("Track not found in queue", code: "CONTENT_UNAVAILABLE")
             }
         } else {
-            invoke.reject("Track not found")
+            invoke.reject("Track not found", code: "CONTENT_UNAVAILABLE")
         }
     } catch {
-        invoke.reject("Invalid arguments for skipToItem: \(error.localizedDescription)")
+        invoke.reject("Invalid arguments for skipToItem: \(error.localizedDescription)", code: "INVALID_ARGUMENT")
     }
   }
   
@@ -419,18 +519,58 @@ public class MusicKitPlugin: Plugin {
         
         invoke.resolve() // RESOLVE IMMEDIATELY (no action to perform)
     } catch {
-        invoke.reject("Invalid arguments for setVolume: \(error.localizedDescription)")
+        invoke.reject("Invalid arguments for setVolume: \(error.localizedDescription)", code: "INVALID_ARGUMENT")
     }
   }
 
+  @objc public func setShuffleMode(_ invoke: Invoke) {
+    print("MusicKit Plugin: setShuffleMode called")
+    do {
+        let args = try invoke.parseArgs(SetModeArgs.self)
+        guard let mode = MPMusicShuffleMode(string: args.mode) else {
+            invoke.reject("Unsupported shuffle mode: \(args.mode)", code: "INVALID_ARGUMENT")
+            return
+        }
+        player.shuffleMode = mode
+        invoke.resolve()
+        trigger("musickit-playback-state-changed", data: convertToJSValue(getCurrentPlaybackState()))
+    } catch {
+        invoke.reject("Invalid arguments for setShuffleMode: \(error.localizedDescription)", code: "INVALID_ARGUMENT")
+    }
+  }
+
+  @objc public func getShuffleMode(_ invoke: Invoke) {
+    invoke.resolve(["mode": player.shuffleMode.toString()])
+  }
+
+  @objc public func setRepeatMode(_ invoke: Invoke) {
+    print("MusicKit Plugin: setRepeatMode called")
+    do {
+        let args = try invoke.parseArgs(SetModeArgs.self)
+        guard let mode = MPMusicRepeatMode(string: args.mode) else {
+            invoke.reject("Unsupported repeat mode: \(args.mode)", code: "INVALID_ARGUMENT")
+            return
+        }
+        player.repeatMode = mode
+        invoke.resolve()
+        trigger("musickit-playback-state-changed", data: convertToJSValue(getCurrentPlaybackState()))
+    } catch {
+        invoke.reject("Invalid arguments for setRepeatMode: \(error.localizedDescription)", code: "INVALID_ARGUMENT")
+    }
+  }
+
+  @objc public func getRepeatMode(_ invoke: Invoke) {
+    invoke.resolve(["mode": player.repeatMode.toString()])
+  }
+
     @objc public func setQueue(_ invoke: Invoke) {
         print("MusicKit Plugin: setQueue called")
         do {
             let args = try invoke.parseArgs(SetQueueArgs.self)
             print("MusicKit Plugin: setQueue args - tracks: \(args.tracks.count), startPlaying: \(args.startPlaying), startPosition: \(args.startPosition)")
             
-            // Store full track objects in shadow queue
-            self.queueManager.updateQueue(with: args.tracks)
+            // Keep the full track objects for metadata lookups
+            self.queuedTracks = args.tracks
             
             // Extract track IDs for MediaPlayer queue
             let trackIds = args.tracks.map { $0.id }
@@ -471,193 +611,91 @@ public class MusicKitPlugin: Plugin {
         }
     }
   
-  @objc public func updateQueue(_ invoke: Invoke) {
-    print("MusicKit Plugin: updateQueue called")
-    do {
-        let args = try invoke.parseArgs(UpdateQueueArgs.self)
-        print("MusicKit Plugin: updateQueue args - tracks: \(args.tracks.count)")
-        
-        // Update the shadow queue
-        queueManager.updateQueue(with: args.tracks)
-        
-        // Update the MediaPlayer queue
-        let trackIds = args.tracks.map { $0.id }
-        let validTrackIds = trackIds.filter { !$0.isEmpty }
-        
-        if !validTrackIds.isEmpty {
-            let queue = MPMusicPlayerStoreQueueDescriptor(storeIDs: validTrackIds)
-            player.setQueue(with: queue)
-            print("MusicKit Plugin: Queue updated with \(validTrackIds.count) tracks")
-        }
-        
-        // Emit queue change event
-        trigger("musickit-queue-changed", data: ["success": true] as [String: JSValue])
-        
-        invoke.resolve(["success": true, "error": ""])
-    } catch {
-        print("MusicKit Plugin: updateQueue error: \(error.localizedDescription)")
-        invoke.reject("Failed to update queue: \(error.localizedDescription)")
-    }
-  }
-  
-  @objc public func appendTracksToQueue(_ invoke: Invoke) {
-    print("MusicKit Plugin: appendTracksToQueue called")
-    do {
-        let args = try invoke.parseArgs(AppendTracksArgs.self)
-        print("MusicKit Plugin: appendTracksToQueue args - tracks: \(args.tracks.count)")
-        
-        // Get current queue and append new tracks
-        var currentQueue = queueManager.getQueue()
-        currentQueue.append(contentsOf: args.tracks)
-        queueManager.updateQueue(with: currentQueue)
-        
-        // Update MediaPlayer queue
-        let trackIds = currentQueue.map { $0.id }
-        let validTrackIds = trackIds.filter { !$0.isEmpty }
-        
-        if !validTrackIds.isEmpty {
-            let queue = MPMusicPlayerStoreQueueDescriptor(storeIDs: validTrackIds)
-            player.setQueue(with: queue)
-            print("MusicKit Plugin: Appended \(args.tracks.count) tracks to queue")
-        }
-        
-        invoke.resolve(["success": true, "error": ""])
-    } catch {
-        print("MusicKit Plugin: appendTracksToQueue error: \(error.localizedDescription)")
-        invoke.reject("Failed to append tracks: \(error.localizedDescription)")
-    }
-  }
-  
-  @objc public func removeTrackFromQueue(_ invoke: Invoke) {
-    print("MusicKit Plugin: removeTrackFromQueue called")
-    do {
-        let args = try invoke.parseArgs(RemoveTrackArgs.self)
-        print("MusicKit Plugin: removeTrackFromQueue args - trackId: \(args.trackId)")
-        
-        // Get current queue and remove the track
-        var currentQueue = queueManager.getQueue()
-        currentQueue.removeAll { $0.id == args.trackId }
-        queueManager.updateQueue(with: currentQueue)
-        
-        // Update MediaPlayer queue
-        let trackIds = currentQueue.map { $0.id }
-        let validTrackIds = trackIds.filter { !$0.isEmpty }
-        
-        if !validTrackIds.isEmpty {
-            let queue = MPMusicPlayerStoreQueueDescriptor(storeIDs: validTrackIds)
-            player.setQueue(with: queue)
-            print("MusicKit Plugin: Removed track from queue")
-        }
-        
-        invoke.resolve(["success": true, "error": ""])
-    } catch {
-        print("MusicKit Plugin: removeTrackFromQueue error: \(error.localizedDescription)")
-        invoke.reject("Failed to remove track: \(error.localizedDescription)")
-    }
-  }
-  
-  @objc public func insertTrackAtPosition(_ invoke: Invoke) {
-    print("MusicKit Plugin: insertTrackAtPosition called")
-    do {
-        let args = try invoke.parseArgs(InsertTrackArgs.self)
-        print("MusicKit Plugin: insertTrackAtPosition args - track: \(args.track.title), position: \(args.position)")
-        
-        // Get current queue and insert the track
-        var currentQueue = queueManager.getQueue()
-        let insertIndex = min(args.position, currentQueue.count)
-        currentQueue.insert(args.track, at: insertIndex)
-        queueManager.updateQueue(with: currentQueue)
-        
-        // Update MediaPlayer queue
-        let trackIds = currentQueue.map { $0.id }
-        let validTrackIds = trackIds.filter { !$0.isEmpty }
-        
-        if !validTrackIds.isEmpty {
-            let queue = MPMusicPlayerStoreQueueDescriptor(storeIDs: validTrackIds)
+    @objc public func setQueueFromContainer(_ invoke: Invoke) {
+        print("MusicKit Plugin: setQueueFromContainer called")
+        do {
+            let args = try invoke.parseArgs(SetQueueFromContainerArgs.self)
+            print("MusicKit Plugin: setQueueFromContainer args - kind: \(args.kind), id: \(args.id), tracks: \(args.tracks.count)")
+
+            // Albums, playlists and stations are queued by their own store ID; an
+            // artist's top songs have none, so they are queued track by track
+            let storeIDs = args.kind == "artist" ? args.tracks.map { $0.id }.filter { !$0.isEmpty } : [args.id]
+            if storeIDs.isEmpty {
+                invoke.resolve(["success": false, "error": "The \(args.kind) has no playable tracks"])
+                return
+            }
+
+            self.queuedTracks = args.tracks
+
+            let queue = MPMusicPlayerStoreQueueDescriptor(storeIDs: storeIDs)
+            if args.startIndex > 0 && args.startIndex < args.tracks.count {
+                queue.startItemID = args.tracks[args.startIndex].id
+            }
+
+            player.shuffleMode = args.shuffle ? .songs : .off
             player.setQueue(with: queue)
-            print("MusicKit Plugin: Inserted track at position \(insertIndex)")
+            player.play()
+            startTimeObserver()
+
+            trigger("musickit-queue-changed", data: ["success": true] as [String: JSValue])
+            invoke.resolve(["success": true, "error": ""])
+        } catch {
+            print("MusicKit Plugin: setQueueFromContainer error: \(error.localizedDescription)")
+            invoke.reject("Invalid arguments for setQueueFromContainer: \(error.localizedDescription)", code: "INVALID_ARGUMENT")
         }
-        
-        invoke.resolve(["success": true, "error": ""])
-    } catch {
-        print("MusicKit Plugin: insertTrackAtPosition error: \(error.localizedDescription)")
-        invoke.reject("Failed to insert track: \(error.localizedDescription)")
     }
-  }
-  
-  @objc public func insertTracksAtPosition(_ invoke: Invoke) {
-    print("MusicKit Plugin: insertTracksAtPosition called")
+            
+  @objc public func rearrangeQueue(_ invoke: Invoke) {
+    print("MusicKit Plugin: rearrangeQueue called")
     do {
-        let args = try invoke.parseArgs(InsertTracksArgs.self)
-        print("MusicKit Plugin: insertTracksAtPosition args - tracks: \(args.tracks.count), position: \(args.position)")
-        
-        // Get current queue and insert the tracks
-        var currentQueue = queueManager.getQueue()
-        let insertIndex = min(args.position, currentQueue.count)
-        currentQueue.insert(contentsOf: args.tracks, at: insertIndex)
-        queueManager.updateQueue(with: currentQueue)
-        
-        // Update MediaPlayer queue
-        let trackIds = currentQueue.map { $0.id }
-        let validTrackIds = trackIds.filter { !$0.isEmpty }
-        
-        if !validTrackIds.isEmpty {
-            let queue = MPMusicPlayerStoreQueueDescriptor(storeIDs: validTrackIds)
-            player.setQueue(with: queue)
-            print("MusicKit Plugin: Inserted \(args.tracks.count) tracks at position \(insertIndex)")
+        let args = try invoke.parseArgs(RearrangeQueueArgs.self)
+        let current = player.indexOfNowPlayingItem
+        guard player.nowPlayingItem != nil, args.position >= 0, args.position < args.tracks.count else {
+            invoke.resolve(["success": false, "error": "No current item to rearrange the queue around"])
+            return
         }
-        
-        invoke.resolve(["success": true, "error": ""])
-    } catch {
-        print("MusicKit Plugin: insertTracksAtPosition error: \(error.localizedDescription)")
-        invoke.reject("Failed to insert tracks: \(error.localizedDescription)")
-    }
-  }
-  
-  @objc public func removeTrackAtPosition(_ invoke: Invoke) {
-    print("MusicKit Plugin: removeTrackAtPosition called")
-    do {
-        let args = try invoke.parseArgs(RemoveTrackAtPositionArgs.self)
-        let position = args.position
-        print("MusicKit Plugin: removeTrackAtPosition args - position: \(position)")
-        
-        // Get current queue and remove the track at the specified position
-        var currentQueue = queueManager.getQueue()
-        if position >= 0 && position < currentQueue.count {
-            currentQueue.remove(at: position)
-            queueManager.updateQueue(with: currentQueue)
-            
-            // Update MediaPlayer queue
-            let trackIds = currentQueue.map { $0.id }
-            let validTrackIds = trackIds.filter { !$0.isEmpty }
-            
-            if !validTrackIds.isEmpty {
-                let queue = MPMusicPlayerStoreQueueDescriptor(storeIDs: validTrackIds)
-                player.setQueue(with: queue)
-                print("MusicKit Plugin: Removed track at position \(position)")
+        let before = args.tracks[..<args.position].map { $0.id }.filter { !$0.isEmpty }
+        let after = args.tracks[(args.position + 1)...].map { $0.id }.filter { !$0.isEmpty }
+
+        // Swap out everything but the now playing item, so it keeps playing undisturbed
+        player.perform(queueTransaction: { queue in
+            for (index, item) in queue.items.enumerated().reversed() where index != current {
+                queue.remove(item)
             }
-            
+            if !before.isEmpty {
+                queue.insert(MPMusicPlayerStoreQueueDescriptor(storeIDs: before), after: nil)
+            }
+            if !after.isEmpty {
+                queue.insert(MPMusicPlayerStoreQueueDescriptor(storeIDs: after), after: queue.items.last)
+            }
+        }, completionHandler: { [weak self] _, error in
+            guard let self = self else { return }
+            if let error = error {
+                print("MusicKit Plugin: rearrangeQueue error: \(error.localizedDescription)")
+                invoke.reject("Failed to rearrange queue: \(error.localizedDescription)", code: self.errorCode(for: error))
+                return
+            }
+            self.queuedTracks = args.tracks
+            self.trigger("musickit-queue-changed", data: ["success": true] as [String: JSValue])
             invoke.resolve(["success": true, "error": ""])
-        } else {
-            invoke.reject("Invalid position: \(position)")
-        }
+        })
     } catch {
-        print("MusicKit Plugin: removeTrackAtPosition error: \(error.localizedDescription)")
-        invoke.reject("Failed to remove track at position: \(error.localizedDescription)")
+        invoke.reject("Invalid arguments for rearrangeQueue: \(error.localizedDescription)", code: "INVALID_ARGUMENT")
     }
   }
-  
+
   @objc public func clearQueue(_ invoke: Invoke) {
     print("MusicKit Plugin: clearQueue called")
     
-    // Clear the shadow queue
-    queueManager.clearQueue()
+    queuedTracks.removeAll()
     
-    // Clear the MediaPlayer queue by setting an empty queue
+    // Stop the current item, then clear the MediaPlayer queue by setting an empty queue
+    player.stop()
     let emptyQueue = MPMusicPlayerStoreQueueDescriptor(storeIDs: [])
     player.setQueue(with: emptyQueue)
     
     print("MusicKit Plugin: Queue cleared successfully")
+    trigger("musickit-queue-changed", data: ["success": true] as [String: JSValue])
     invoke.resolve(["success": true, "error": ""])
   }
   
@@ -670,30 +708,12 @@ public class MusicKitPlugin: Plugin {
     }
     
     let trackId = currentItem.playbackStoreID
-    if let currentTrack = queueManager.findTrack(byId: trackId) {
-        let result: [String: Any] = [
-            "id": currentTrack.id,
-            "title": currentTrack.title,
-            "artist": currentTrack.artistName,
-            "album": currentTrack.albumName,
-            "duration": Double(currentTrack.durationInMillis) / 1000.0,
-            "artworkUrl": currentTrack.artwork,
-            "isExplicit": currentTrack.isExplicit,
-            "isPlayable": currentTrack.isPlayable
-        ]
+    if let currentTrack = findTrack(byId: trackId) {
+        let result: [String: Any] = currentTrack.dictionary
         invoke.resolve(result)
     } else {
         // Fallback to MPMediaItem data
-        let result: [String: Any] = [
-            "id": currentItem.playbackStoreID,
-            "title": currentItem.title ?? "",
-            "artist": currentItem.artist ?? "",
-            "album": currentItem.albumTitle ?? "",
-            "duration": currentItem.playbackDuration,
-            "artworkUrl": "",
-            "isExplicit": false,
-            "isPlayable": true
-        ]
+        let result: [String: Any] = FullTrackData(item: currentItem).dictionary
         invoke.resolve(result)
     }
   }
@@ -711,19 +731,10 @@ public class MusicKitPlugin: Plugin {
     }
     
     let trackId = currentItem.playbackStoreID
-    let currentTrackIndex = player.indexOfNowPlayingItem
+    let currentTrackIndex = nowPlayingPosition()
     
-    if let currentTrack = queueManager.findTrack(byId: trackId) {
-        let trackInfo: [String: Any] = [
-            "id": currentTrack.id,
-            "title": currentTrack.title,
-            "artist": currentTrack.artistName,
-            "album": currentTrack.albumName,
-            "duration": Double(currentTrack.durationInMillis) / 1000.0,
-            "artworkUrl": currentTrack.artwork,
-            "isExplicit": currentTrack.isExplicit,
-            "isPlayable": currentTrack.isPlayable
-        ]
+    if let currentTrack = findTrack(byId: trackId) {
+        let trackInfo: [String: Any] = currentTrack.dictionary
         
         let result: [String: Any] = [
             "currentTrack": trackInfo,
@@ -732,16 +743,7 @@ public class MusicKitPlugin: Plugin {
         invoke.resolve(result)
     } else {
         // Fallback to MPMediaItem data
-        let trackInfo: [String: Any] = [
-            "id": currentItem.playbackStoreID,
-            "title": currentItem.title ?? "",
-            "artist": currentItem.artist ?? "",
-            "album": currentItem.albumTitle ?? "",
-            "duration": currentItem.playbackDuration,
-            "artworkUrl": "",
-            "isExplicit": false,
-            "isPlayable": true
-        ]
+        let trackInfo: [String: Any] = FullTrackData(item: currentItem).dictionary
         
         let result: [String: Any] = [
             "currentTrack": trackInfo,
@@ -774,27 +776,12 @@ public class MusicKitPlugin: Plugin {
     if let item = currentItem {
         // Find the corresponding track in our shadow queue
         let trackId = item.playbackStoreID
-        if let shadowTrack = queueManager.findTrack(byId: trackId) {
+        if let shadowTrack = findTrack(byId: trackId) {
             // Use complete metadata from shadow queue
-            trackData = [
-                "id": shadowTrack.id,
-                "title": shadowTrack.title,
-                "artistName": shadowTrack.artistName,
-                "albumName": shadowTrack.albumName,
-                "genreNames": shadowTrack.genreNames,
-                "durationInMillis": shadowTrack.durationInMillis,
-                "artwork": shadowTrack.artwork
-            ]
+            trackData = shadowTrack.dictionary
         } else {
             // Fallback to MPMediaItem data if not found in shadow queue
-            trackData = [
-                "id": item.playbackStoreID,
-                "title": item.title ?? "",
-                "artistName": item.artist ?? "",
-                "albumName": item.albumTitle ?? "",
-                "genreNames": item.genre ?? "",
-                "durationInMillis": Int(duration * 1000)
-            ]
+            trackData = FullTrackData(item: item).dictionary
         }
     }
     
@@ -805,73 +792,15 @@ public class MusicKitPlugin: Plugin {
         "currentTime": currentTime,
         "duration": duration,
         "progress": progress,
-        "queuePosition": player.indexOfNowPlayingItem,
-        "shuffleMode": player.shuffleMode == .default ? "off" : "on",
-        "repeatMode": player.repeatMode == .default ? "none" : "all",
+        "queuePosition": nowPlayingPosition(),
+        "shuffleMode": player.shuffleMode.toString(),
+        "repeatMode": player.repeatMode.toString(),
         "volume": 1.0
     ]
     
     return result
   }
-
-  @objc public func insertTrackNext(_ invoke: Invoke) {
-    print("MusicKit Plugin: insertTrackNext called")
-    do {
-        let args = try invoke.parseArgs(InsertTrackArgs.self)
-        print("MusicKit Plugin: insertTrackNext args - track: \(args.track.title)")
-        
-        // Get current queue and insert the track after the current position
-        var currentQueue = queueManager.getQueue()
-        let currentPosition = player.indexOfNowPlayingItem
-        let insertIndex = min(currentPosition + 1, currentQueue.count)
-        currentQueue.insert(args.track, at: insertIndex)
-        queueManager.updateQueue(with: currentQueue)
-        
-        // Update MediaPlayer queue
-        let trackIds = currentQueue.map { $0.id }
-        let validTrackIds = trackIds.filter { !$0.isEmpty }
-        
-        if !validTrackIds.isEmpty {
-            let queue = MPMusicPlayerStoreQueueDescriptor(storeIDs: validTrackIds)
-            player.setQueue(with: queue)
-            print("MusicKit Plugin: Inserted track next at position \(insertIndex)")
-        }
-        
-        invoke.resolve(["success": true, "error": ""])
-    } catch {
-        print("MusicKit Plugin: insertTrackNext error: \(error.localizedDescription)")
-        invoke.reject("Failed to insert track next: \(error.localizedDescription)")
-    }
-  }
   
-  @objc public func insertTrackLast(_ invoke: Invoke) {
-    print("MusicKit Plugin: insertTrackLast called")
-    do {
-        let args = try invoke.parseArgs(InsertTrackArgs.self)
-        print("MusicKit Plugin: insertTrackLast args - track: \(args.track.title)")
-        
-        // Get current queue and append the track
-        var currentQueue = queueManager.getQueue()
-        currentQueue.append(args.track)
-        queueManager.updateQueue(with: currentQueue)
-        
-        // Update MediaPlayer queue
-        let trackIds = currentQueue.map { $0.id }
-        let validTrackIds = trackIds.filter { !$0.isEmpty }
-        
-        if !validTrackIds.isEmpty {
-            let queue = MPMusicPlayerStoreQueueDescriptor(storeIDs: validTrackIds)
-            player.setQueue(with: queue)
-            print("MusicKit Plugin: Inserted track last")
-        }
-        
-        invoke.resolve(["success": true, "error": ""])
-    } catch {
-        print("MusicKit Plugin: insertTrackLast error: \(error.localizedDescription)")
-        invoke.reject("Failed to insert track last: \(error.localizedDescription)")
-    }
-  }
-
     @objc func handlePlaybackStateDidChange(notification: NSNotification) {
         let currentItem = player.nowPlayingItem
         let currentTime = player.currentPlaybackTime
@@ -886,25 +815,10 @@ public class MusicKitPlugin: Plugin {
         var trackData: [String: Any]? = nil
         if let item = currentItem {
             let trackId = item.playbackStoreID
-            if let shadowTrack = queueManager.findTrack(byId: trackId) {
-                trackData = [
-                    "id": shadowTrack.id,
-                    "title": shadowTrack.title,
-                    "artistName": shadowTrack.artistName,
-                    "albumName": shadowTrack.albumName,
-                    "genreNames": shadowTrack.genreNames,
-                    "durationInMillis": shadowTrack.durationInMillis,
-                    "artwork": shadowTrack.artwork
-                ]
+            if let shadowTrack = findTrack(byId: trackId) {
+                trackData = shadowTrack.dictionary
             } else {
-                trackData = [
-                    "id": item.playbackStoreID,
-                    "title": item.title ?? "",
-                    "artistName": item.artist ?? "",
-                    "albumName": item.albumTitle ?? "",
-                    "genreNames": item.genre ?? "",
-                    "durationInMillis": Int(duration * 1000)
-                ]
+                trackData = FullTrackData(item: item).dictionary
             }
         }
         
@@ -915,9 +829,9 @@ public class MusicKitPlugin: Plugin {
             "currentTime": currentTime,
             "duration": duration,
             "progress": progress,
-            "queuePosition": player.indexOfNowPlayingItem,
-            "shuffleMode": player.shuffleMode == .default ? "off" : "on",
-            "repeatMode": player.repeatMode == .default ? "none" : "all",
+            "queuePosition": nowPlayingPosition(),
+            "shuffleMode": player.shuffleMode.toString(),
+            "repeatMode": player.repeatMode.toString(),
             "volume": 1.0
         ]
         
@@ -939,25 +853,10 @@ public class MusicKitPlugin: Plugin {
         var trackData: [String: Any]? = nil
         if let item = currentItem {
             let trackId = item.playbackStoreID
-            if let shadowTrack = queueManager.findTrack(byId: trackId) {
-                trackData = [
-                    "id": shadowTrack.id,
-                    "title": shadowTrack.title,
-                    "artistName": shadowTrack.artistName,
-                    "albumName": shadowTrack.albumName,
-                    "genreNames": shadowTrack.genreNames,
-                    "durationInMillis": shadowTrack.durationInMillis,
-                    "artwork": shadowTrack.artwork
-                ]
+            if let shadowTrack = findTrack(byId: trackId) {
+                trackData = shadowTrack.dictionary
             } else {
-                trackData = [
-                    "id": item.playbackStoreID,
-                    "title": item.title ?? "",
-                    "artistName": item.artist ?? "",
-                    "albumName": item.albumTitle ?? "",
-                    "genreNames": item.genre ?? "",
-                    "durationInMillis": Int(duration * 1000)
-                ]
+                trackData = FullTrackData(item: item).dictionary
             }
         }
         
@@ -968,9 +867,9 @@ public class MusicKitPlugin: Plugin {
             "currentTime": currentTime,
             "duration": duration,
             "progress": progress,
-            "queuePosition": player.indexOfNowPlayingItem,
-            "shuffleMode": player.shuffleMode == .default ? "off" : "on",
-            "repeatMode": player.repeatMode == .default ? "none" : "all",
+            "queuePosition": nowPlayingPosition(),
+            "shuffleMode": player.shuffleMode.toString(),
+            "repeatMode": player.repeatMode.toString(),
             "volume": 1.0
         ]
         
@@ -978,6 +877,37 @@ public class MusicKitPlugin: Plugin {
         trigger("musickit-track-changed", data: jsData)
     }
 
+    private func findTrack(byId id: String) -> FullTrackData? {
+        return queuedTracks.first { $0.id == id }
+    }
+
+    /// The now playing item's index in `queuedTracks`.
+    ///
+    /// `indexOfNowPlayingItem` follows the play order, which is not the queued order
+    /// while shuffling, so the item is looked up by its store ID instead.
+    private func nowPlayingPosition() -> Int {
+        guard player.shuffleMode != .off, let item = player.nowPlayingItem else {
+            return player.indexOfNowPlayingItem
+        }
+        return queuedTracks.firstIndex { $0.id == item.playbackStoreID } ?? player.indexOfNowPlayingItem
+    }
+
+    /// Maps a native error onto the plugin's error codes, so the Rust side can branch on it.
+    private func errorCode(for error: Error) -> String? {
+        if error is URLError {
+            return "NETWORK"
+        }
+        if let error = error as? MusicTokenRequestError {
+            switch error {
+            case .permissionDenied, .userNotSignedIn, .userTokenRevoked, .privacyAcknowledgementRequired:
+                return "NOT_AUTHORIZED"
+            default:
+                return nil
+            }
+        }
+        return nil
+    }
+
     private func convertToJSValue(_ state: [String: Any]) -> [String: JSValue] {
         var jsData: [String: JSValue] = [:]
         for (key, value) in state {
@@ -991,6 +921,8 @@ public class MusicKitPlugin: Plugin {
                 jsData[key] = doubleValue as JSValue
             } else if let dictValue = value as? [String: Any] {
                 jsData[key] = convertToJSValue(dictValue) as JSValue
+            } else if let stringsValue = value as? [String] {
+                jsData[key] = stringsValue.map { $0 as JSValue } as JSValue
             } else {
                 // Fallback for any other types
                 jsData[key] = "\(value)" as JSValue
@@ -1006,27 +938,12 @@ public class MusicKitPlugin: Plugin {
 
         // Find the corresponding track in our shadow queue
         let trackId = item.playbackStoreID
-        if let shadowTrack = queueManager.findTrack(byId: trackId) {
+        if let shadowTrack = findTrack(byId: trackId) {
             // Use complete metadata from shadow queue
-            return [
-                "id": shadowTrack.id as JSValue,
-                "title": shadowTrack.title as JSValue,
-                "artistName": shadowTrack.artistName as JSValue,
-                "albumName": shadowTrack.albumName as JSValue,
-                "genreNames": shadowTrack.genreNames as JSValue,
-                "durationInMillis": shadowTrack.durationInMillis as JSValue,
-                "artwork": shadowTrack.artwork as JSValue
-            ]
+            return convertToJSValue(shadowTrack.dictionary)
         } else {
             // Fallback to MPMediaItem data if not found in shadow queue
-            return [
-                "id": item.playbackStoreID as JSValue,
-                "title": (item.title ?? "") as JSValue,
-                "artistName": (item.artist ?? "") as JSValue,
-                "albumName": (item.albumTitle ?? "") as JSValue,
-                "genreNames": (item.genre ?? "") as JSValue,
-                "durationInMillis": Int(item.playbackDuration * 1000) as JSValue
-            ]
+            return convertToJSValue(FullTrackData(item: item).dictionary)
         }
     }
     
@@ -1074,10 +991,45 @@ public class MusicKitPlugin: Plugin {
         timeObserver = nil
         print("MusicKit Plugin: Time observer stopped")
     }
-    
-    @objc func registerListener(_ invoke: Invoke) {
-        print("MusicKit Plugin: registerListener called")
-        invoke.resolve()
+}
+
+extension MPMusicShuffleMode {
+    init?(string: String) {
+        switch string {
+        case "off": self = .off
+        case "songs", "on": self = .songs
+        case "albums": self = .albums
+        default: return nil
+        }
+    }
+
+    func toString() -> String {
+        switch self {
+        case .off, .default: return "off"
+        case .songs: return "songs"
+        case .albums: return "albums"
+        @unknown default: return "off"
+        }
+    }
+}
+
+extension MPMusicRepeatMode {
+    init?(string: String) {
+        switch string {
+        case "none", "off": self = .none
+        case "one": self = .one
+        case "all": self = .all
+        default: return nil
+        }
+    }
+
+    func toString() -> String {
+        switch self {
+        case .none, .default: return "none"
+        case .one: return "one"
+        case .all: return "all"
+        @unknown default: return "none"
+        }
     }
 }
 
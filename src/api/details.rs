@@ -0,0 +1,407 @@
+//! Albums, artists and playlists with their relationships.
+
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use serde_json::Value;
+
+use super::{Album, AppleMusicClient, Artist, Page, Playlist, Resource, Song};
+use crate::{models::MusicKitTrack, Error, Result};
+
+/// Which album, artist or playlist to fetch, and what to fetch with it.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DetailRequest {
+    pub id: String,
+    /// Relationships to include beyond the ones the details always carry, e.g.
+    /// `artists` for an album or `playlists` for an artist.
+    #[serde(default)]
+    pub include: Vec<String>,
+    /// Extended attributes to request, e.g. `editorialVideo` or `artistBio`. They
+    /// appear in the resource's `attributes.extra`.
+    #[serde(default)]
+    pub extend: Vec<String>,
+    /// Fetches from this storefront instead of the client's.
+    pub storefront: Option<String>,
+    /// Fetches the resource from the user's library instead of the catalog.
+    #[serde(default)]
+    pub library: bool,
+    /// The most items to load per relationship. Every page is loaded when unset.
+    pub max_items: Option<usize>,
+}
+
+impl DetailRequest {
+    pub fn new(id: impl Into<String>) -> Self {
+        Self {
+            id: id.into(),
+            ..Default::default()
+        }
+    }
+}
+
+/// An album with its tracks.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AlbumDetails {
+    /// The album, with the relationships not listed here left in `relationships`.
+    pub album: Album,
+    pub tracks: Vec<MusicKitTrack>,
+    /// The album's artists, when `artists` is included.
+    pub artists: Vec<Artist>,
+}
+
+/// An artist with their albums and top songs.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ArtistDetails {
+    /// The artist, with the relationships not listed here left in `relationships`.
+    pub artist: Artist,
+    pub albums: Vec<Album>,
+    /// The artist's playlists, when `playlists` is included.
+    pub playlists: Vec<Playlist>,
+    /// The artist's most played songs. Empty for library artists.
+    pub top_songs: Vec<MusicKitTrack>,
+}
+
+/// A playlist with its tracks.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PlaylistDetails {
+    /// The playlist, with the relationships not listed here left in `relationships`.
+    pub playlist: Playlist,
+    pub tracks: Vec<MusicKitTrack>,
+}
+
+impl AppleMusicClient {
+    /// Fetches an album and every page of its tracks.
+    pub async fn get_album(&self, request: &DetailRequest) -> Result<AlbumDetails> {
+        let mut album: Album = self.get_resource("albums", request, &["tracks"]).await?;
+        let tracks: Vec<Song> = self
+            .get_relationship(&mut album, "tracks", request.max_items)
+            .await?;
+        let artists = self
+            .get_relationship(&mut album, "artists", request.max_items)
+            .await?;
+        Ok(AlbumDetails {
+            album,
+            tracks: tracks.iter().map(MusicKitTrack::from).collect(),
+            artists,
+        })
+    }
+
+    /// Fetches an artist, every page of their albums and, for catalog artists, their
+    /// top songs.
+    pub async fn get_artist(&self, request: &DetailRequest) -> Result<ArtistDetails> {
+        let mut artist: Artist = self.get_resource("artists", request, &["albums"]).await?;
+        let albums = self
+            .get_relationship(&mut artist, "albums", request.max_items)
+            .await?;
+        let playlists = self
+            .get_relationship(&mut artist, "playlists", request.max_items)
+            .await?;
+        let top_songs = if request.library {
+            Vec::new()
+        } else {
+            self.get_top_songs(request).await?
+        };
+        Ok(ArtistDetails {
+            artist,
+            albums,
+            playlists,
+            top_songs,
+        })
+    }
+
+    /// Fetches a catalog artist's most played songs.
+    pub async fn get_top_songs(&self, request: &DetailRequest) -> Result<Vec<MusicKitTrack>> {
+        let path = self.catalog_path(
+            request.storefront.as_deref(),
+            &format!("artists/{}/view/top-songs", request.id),
+        );
+        let songs: Vec<Song> = self.get_all(&path, &[], request.max_items).await?;
+        Ok(songs.iter().map(MusicKitTrack::from).collect())
+    }
+
+    /// Fetches a playlist and every page of its tracks.
+    pub async fn get_playlist(&self, request: &DetailRequest) -> Result<PlaylistDetails> {
+        let mut playlist: Playlist = self.get_resource("playlists", request, &["tracks"]).await?;
+        let tracks: Vec<Song> = self
+            .get_relationship(&mut playlist, "tracks", request.max_items)
+            .await?;
+        Ok(PlaylistDetails {
+            playlist,
+            tracks: tracks.iter().map(MusicKitTrack::from).collect(),
+        })
+    }
+
+    /// Fetches the `kind` resource `request.id`, always including `required`.
+    async fn get_resource<A: DeserializeOwned>(
+        &self,
+        kind: &str,
+        request: &DetailRequest,
+        required: &[&str],
+    ) -> Result<Resource<A>> {
+        let path = if request.library {
+            format!("/v1/me/library/{kind}/{}", request.id)
+        } else {
+            self.catalog_path(
+                request.storefront.as_deref(),
+                &format!("{kind}/{}", request.id),
+            )
+        };
+        let mut include: Vec<&str> = required.to_vec();
+        for relationship in &request.include {
+            if !include.contains(&relationship.as_str()) {
+                include.push(relationship);
+            }
+        }
+        let mut query = vec![("include", include.join(","))];
+        if !request.extend.is_empty() {
+            query.push(("extend", request.extend.join(",")));
+        }
+        let page: Page<Resource<A>> = self.get(&path, &query).await?;
+        page.data.into_iter().next().ok_or_else(|| Error::NotFound {
+            kind: kind.to_string(),
+            id: request.id.clone(),
+        })
+    }
+
+    /// Takes the `name` relationship out of `resource` and loads its remaining pages.
+    ///
+    /// Returns an empty list when the relationship was not included.
+    async fn get_relationship<A, T: DeserializeOwned>(
+        &self,
+        resource: &mut Resource<A>,
+        name: &str,
+        max_items: Option<usize>,
+    ) -> Result<Vec<T>> {
+        let Some(relationship) = resource
+            .relationships
+            .as_mut()
+            .and_then(Value::as_object_mut)
+            .and_then(|relationships| relationships.remove(name))
+        else {
+            return Ok(Vec::new());
+        };
+        let page: Page<T> = serde_json::from_value(relationship).map_err(|error| {
+            Error::InvalidResponse(format!("invalid {name} relationship: {error}"))
+        })?;
+        self.collect_pages(page, max_items).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::{json, Value};
+
+    use super::*;
+    use crate::api::stand_in::StandIn;
+
+    fn ok(body: Value) -> (u16, String) {
+        (200, body.to_string())
+    }
+
+    fn song(id: &str) -> Value {
+        json!({
+            "id": id,
+            "type": "songs",
+            "attributes": {
+                "name": format!("Song {id}"),
+                "artistName": "Artist",
+                "albumName": "Album",
+                "durationInMillis": 180500,
+                "contentRating": "explicit",
+                "playParams": { "id": id, "kind": "song" },
+            },
+        })
+    }
+
+    fn ids(tracks: &[MusicKitTrack]) -> Vec<&str> {
+        tracks.iter().map(|track| track.id.as_str()).collect()
+    }
+
+    #[tokio::test]
+    async fn albums_include_extend_and_load_every_page_of_tracks() {
+        let mut server = StandIn::start(vec![
+            ok(json!({ "data": [{
+                "id": "1",
+                "type": "albums",
+                "attributes": {
+                    "name": "Album",
+                    "artistName": "Artist",
+                    "editorialVideo": { "motionSquare": {} },
+                },
+                "relationships": {
+                    "tracks": {
+                        "data": [song("s1")],
+                        "next": "/v1/catalog/us/albums/1/tracks?offset=1",
+                    },
+                    "artists": { "data": [
+                        { "id": "a1", "type": "artists", "attributes": { "name": "Artist" } }
+                    ] },
+                },
+            }] })),
+            ok(json!({ "data": [song("s2")] })),
+        ])
+        .await;
+        let client = server.client("token");
+        let request = DetailRequest {
+            include: vec!["artists".to_string(), "tracks".to_string()],
+            extend: vec!["editorialVideo".to_string()],
+            ..DetailRequest::new("1")
+        };
+
+        let details = client.get_album(&request).await.unwrap();
+        assert_eq!(
+            server.request_line().await,
+            "GET /v1/catalog/us/albums/1?include=tracks%2Cartists&extend=editorialVideo HTTP/1.1"
+        );
+        assert_eq!(
+            server.request_line().await,
+            "GET /v1/catalog/us/albums/1/tracks?offset=1 HTTP/1.1"
+        );
+        assert_eq!(ids(&details.tracks), ["s1", "s2"]);
+        assert_eq!(details.artists.len(), 1);
+        let attributes = details.album.attributes.unwrap();
+        assert!(attributes.extra.contains_key("editorialVideo"));
+        assert_eq!(details.album.relationships, Some(json!({})));
+
+        let track = &details.tracks[0];
+        assert_eq!(track.title, "Song s1");
+        assert_eq!(track.artist, "Artist");
+        assert_eq!(track.album, "Album");
+        assert_eq!(track.duration, 180.5);
+        assert_eq!(track.catalog_id.as_deref(), Some("s1"));
+        assert!(track.is_explicit);
+        assert!(track.is_playable);
+    }
+
+    #[tokio::test]
+    async fn long_library_playlists_follow_next_until_the_last_page() {
+        let library_song = |id: &str| {
+            json!({
+                "id": id,
+                "type": "library-songs",
+                "attributes": {
+                    "name": id,
+                    "artistName": "Artist",
+                    "playParams": {
+                        "id": id,
+                        "kind": "song",
+                        "catalogId": "c.1",
+                        "isLibrary": true,
+                    },
+                },
+            })
+        };
+        let mut server = StandIn::start(vec![
+            ok(json!({ "data": [{
+                "id": "p.1",
+                "type": "library-playlists",
+                "attributes": { "name": "Mix" },
+                "relationships": { "tracks": {
+                    "data": [library_song("i.1")],
+                    "next": "/v1/me/library/playlists/p.1/tracks?offset=1",
+                } },
+            }] })),
+            ok(json!({
+                "data": [library_song("i.2")],
+                "next": "/v1/me/library/playlists/p.1/tracks?offset=2",
+            })),
+            ok(json!({ "data": [library_song("i.3")] })),
+        ])
+        .await;
+        let client = server.client("token");
+        let request = DetailRequest {
+            library: true,
+            ..DetailRequest::new("p.1")
+        };
+
+        let details = client.get_playlist(&request).await.unwrap();
+        assert_eq!(
+            server.request_line().await,
+            "GET /v1/me/library/playlists/p.1?include=tracks HTTP/1.1"
+        );
+        assert_eq!(
+            server.request_line().await,
+            "GET /v1/me/library/playlists/p.1/tracks?offset=1 HTTP/1.1"
+        );
+        assert_eq!(
+            server.request_line().await,
+            "GET /v1/me/library/playlists/p.1/tracks?offset=2 HTTP/1.1"
+        );
+        assert_eq!(ids(&details.tracks), ["i.1", "i.2", "i.3"]);
+        assert_eq!(details.tracks[0].library_id.as_deref(), Some("i.1"));
+        assert_eq!(details.tracks[0].catalog_id.as_deref(), Some("c.1"));
+    }
+
+    #[tokio::test]
+    async fn artists_carry_their_albums_playlists_and_top_songs() {
+        let mut server = StandIn::start(vec![
+            ok(json!({ "data": [{
+                "id": "7",
+                "type": "artists",
+                "attributes": { "name": "Artist" },
+                "relationships": {
+                    "albums": { "data": [{
+                        "id": "1",
+                        "type": "albums",
+                        "attributes": { "name": "Album", "artistName": "Artist" },
+                    }] },
+                    "playlists": { "data": [{
+                        "id": "pl.1",
+                        "type": "playlists",
+                        "attributes": { "name": "Essentials" },
+                    }] },
+                },
+            }] })),
+            ok(json!({ "data": [song("s1"), song("s2")] })),
+        ])
+        .await;
+        let client = server.client("token");
+        let request = DetailRequest {
+            include: vec!["playlists".to_string()],
+            storefront: Some("gb".to_string()),
+            ..DetailRequest::new("7")
+        };
+
+        let details = client.get_artist(&request).await.unwrap();
+        assert_eq!(
+            server.request_line().await,
+            "GET /v1/catalog/gb/artists/7?include=albums%2Cplaylists HTTP/1.1"
+        );
+        assert_eq!(
+            server.request_line().await,
+            "GET /v1/catalog/gb/artists/7/view/top-songs HTTP/1.1"
+        );
+        assert_eq!(details.albums.len(), 1);
+        assert_eq!(details.playlists.len(), 1);
+        assert_eq!(ids(&details.top_songs), ["s1", "s2"]);
+    }
+
+    #[tokio::test]
+    async fn missing_resources_and_malformed_relationships_are_not_network_errors() {
+        let server = StandIn::start(vec![
+            ok(json!({ "data": [] })),
+            ok(json!({ "data": [{
+                "id": "1",
+                "type": "albums",
+                "relationships": { "tracks": { "data": "not a list" } },
+            }] })),
+        ])
+        .await;
+        let client = server.client("token");
+
+        let error = client
+            .get_album(&DetailRequest::new("1"))
+            .await
+            .unwrap_err();
+        assert!(
+            matches!(&error, Error::NotFound { kind, id } if kind == "albums" && id == "1"),
+            "{error:?}"
+        );
+        let error = client
+            .get_album(&DetailRequest::new("1"))
+            .await
+            .unwrap_err();
+        assert!(matches!(error, Error::InvalidResponse(_)), "{error:?}");
+    }
+}
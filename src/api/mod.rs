@@ -0,0 +1,380 @@
+//! A native client for the Apple Music Web API.
+//!
+//! [`AppleMusicClient`] is built from the same developer token, user token and
+//! storefront the plugin already manages, and decodes responses into the typed
+//! resources in [`resources`].
+
+mod details;
+mod resources;
+mod search;
+#[cfg(test)]
+pub(crate) mod stand_in;
+mod suggestions;
+
+pub use details::*;
+pub use resources::*;
+pub use search::*;
+pub use suggestions::*;
+
+use reqwest::{RequestBuilder, Response, Url};
+use serde::{de::DeserializeOwned, Deserialize};
+
+use crate::{Error, MusicKitBackend, Result};
+
+/// The production Apple Music API host.
+pub const DEFAULT_BASE_URL: &str = "https://api.music.apple.com";
+
+/// The storefront used when none has been resolved yet.
+pub const DEFAULT_STOREFRONT: &str = "us";
+
+/// Async client for the Apple Music Web API.
+#[derive(Debug, Clone)]
+pub struct AppleMusicClient {
+    http: reqwest::Client,
+    base_url: Url,
+    developer_token: String,
+    user_token: Option<String>,
+    storefront: String,
+}
+
+impl AppleMusicClient {
+    /// Creates a client for the production API in the default storefront.
+    pub fn new(developer_token: impl Into<String>) -> Self {
+        Self {
+            http: reqwest::Client::new(),
+            base_url: Url::parse(DEFAULT_BASE_URL).expect("default base URL is valid"),
+            developer_token: developer_token.into(),
+            user_token: None,
+            storefront: DEFAULT_STOREFRONT.to_string(),
+        }
+    }
+
+    /// Creates a client from the tokens and storefront a backend currently holds.
+    pub fn from_backend(backend: &dyn MusicKitBackend) -> Result<Self> {
+        let developer_token = backend
+            .get_developer_token()?
+            .filter(|token| !token.is_empty())
+            .ok_or(Error::MissingDeveloperToken)?;
+        Self::new(developer_token).with_backend_session(backend)
+    }
+
+    /// Applies the user token and storefront a backend currently holds.
+    pub fn with_backend_session(mut self, backend: &dyn MusicKitBackend) -> Result<Self> {
+        if let Some(token) = backend.get_user_token()?.token {
+            self = self.with_user_token(token);
+        }
+        if let Some(storefront) = backend.get_storefront_id()? {
+            self = self.with_storefront(storefront);
+        }
+        Ok(self)
+    }
+
+    /// Sets the Music-User-Token sent with every request. Empty tokens are ignored.
+    pub fn with_user_token(mut self, token: impl Into<String>) -> Self {
+        self.user_token = Some(token.into()).filter(|token| !token.is_empty());
+        self
+    }
+
+    /// Sets the storefront used for catalog paths. Empty storefronts are ignored.
+    pub fn with_storefront(mut self, storefront: impl Into<String>) -> Self {
+        let storefront = storefront.into();
+        if !storefront.is_empty() {
+            self.storefront = storefront;
+        }
+        self
+    }
+
+    /// Points the client at another host, such as a local stand-in server in tests.
+    pub fn with_base_url(mut self, base_url: &str) -> Result<Self> {
+        self.base_url = Url::parse(base_url).map_err(|error| {
+            Error::InvalidConfig(format!("invalid base URL {base_url}: {error}"))
+        })?;
+        Ok(self)
+    }
+
+    /// Uses a preconfigured `reqwest` client, e.g. one with custom timeouts or proxies.
+    pub fn with_http_client(mut self, http: reqwest::Client) -> Self {
+        self.http = http;
+        self
+    }
+
+    pub fn base_url(&self) -> &Url {
+        &self.base_url
+    }
+
+    pub fn storefront(&self) -> &str {
+        &self.storefront
+    }
+
+    pub fn has_user_token(&self) -> bool {
+        self.user_token.is_some()
+    }
+
+    /// Builds the path of a catalog endpoint, e.g. `catalog_path(None, "songs")`
+    /// gives `/v1/catalog/us/songs`. `storefront` overrides the client's storefront.
+    pub fn catalog_path(&self, storefront: Option<&str>, suffix: &str) -> String {
+        let storefront = storefront.unwrap_or(&self.storefront);
+        format!(
+            "/v1/catalog/{storefront}/{}",
+            suffix.trim_start_matches('/')
+        )
+    }
+
+    /// Sends an authorized GET for `path` (relative to the base URL) and decodes the
+    /// JSON body.
+    pub async fn get<T: DeserializeOwned>(
+        &self,
+        path: &str,
+        query: &[(&str, String)],
+    ) -> Result<T> {
+        let request = self.request(path)?.query(query);
+        let response = Self::check(request.send().await?).await?;
+        Ok(response.json().await?)
+    }
+
+    /// Fetches one page of a paginated resource collection.
+    pub async fn get_page<T: DeserializeOwned>(
+        &self,
+        path: &str,
+        query: &[(&str, String)],
+    ) -> Result<Page<T>> {
+        self.get(path, query).await
+    }
+
+    /// Follows the `next` cursor of `page`, or returns `None` on the last page.
+    pub async fn next_page<T: DeserializeOwned>(&self, page: &Page<T>) -> Result<Option<Page<T>>> {
+        match &page.next {
+            Some(next) => self.get(next, &[]).await.map(Some),
+            None => Ok(None),
+        }
+    }
+
+    /// Fetches a resource collection and every following page, concatenating the data.
+    ///
+    /// `max_items` stops paging once at least that many items were collected.
+    pub async fn get_all<T: DeserializeOwned>(
+        &self,
+        path: &str,
+        query: &[(&str, String)],
+        max_items: Option<usize>,
+    ) -> Result<Vec<T>> {
+        let page = self.get_page(path, query).await?;
+        self.collect_pages(page, max_items).await
+    }
+
+    /// Collects the data of `page` and every page following it.
+    ///
+    /// `max_items` stops paging once at least that many items were collected.
+    pub async fn collect_pages<T: DeserializeOwned>(
+        &self,
+        mut page: Page<T>,
+        max_items: Option<usize>,
+    ) -> Result<Vec<T>> {
+        let mut items = Vec::new();
+        loop {
+            let next = page.next.take();
+            items.append(&mut page.data);
+            let done = max_items.is_some_and(|max| items.len() >= max);
+            match next {
+                Some(next) if !done => page = self.get(&next, &[]).await?,
+                _ => break,
+            }
+        }
+        if let Some(max) = max_items {
+            items.truncate(max);
+        }
+        Ok(items)
+    }
+
+    fn request(&self, path: &str) -> Result<RequestBuilder> {
+        let url = self
+            .base_url
+            .join(path)
+            .map_err(|error| Error::Http(format!("invalid request path {path}: {error}")))?;
+        let mut request = self.http.get(url).bearer_auth(&self.developer_token);
+        if let Some(user_token) = &self.user_token {
+            request = request.header("Music-User-Token", user_token);
+        }
+        Ok(request)
+    }
+
+    /// Turns non-success responses into [`Error::Api`], using the first entry of the
+    /// Apple Music `errors` array as the message when there is one.
+    async fn check(response: Response) -> Result<Response> {
+        let status = response.status();
+        if status.is_success() {
+            return Ok(response);
+        }
+        let body = response.text().await.unwrap_or_default();
+        let message = serde_json::from_str::<ErrorResponse>(&body)
+            .ok()
+            .and_then(|errors| errors.errors.into_iter().next())
+            .map(|error| error.detail.or(error.title).unwrap_or_default())
+            .filter(|message| !message.is_empty())
+            .unwrap_or_else(|| status.to_string());
+        Err(Error::Api {
+            status: status.as_u16(),
+            message,
+        })
+    }
+}
+
+#[derive(Deserialize)]
+struct ErrorResponse {
+    #[serde(default)]
+    errors: Vec<ApiError>,
+}
+
+#[derive(Deserialize)]
+struct ApiError {
+    title: Option<String>,
+    detail: Option<String>,
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::{json, Value};
+
+    use super::{stand_in::StandIn, *};
+    use crate::ErrorCode;
+
+    fn ok(body: Value) -> (u16, String) {
+        (200, body.to_string())
+    }
+
+    #[tokio::test]
+    async fn requests_carry_the_developer_and_user_tokens() {
+        let mut server = StandIn::start(vec![ok(json!({})), ok(json!({}))]).await;
+
+        let client = server
+            .client("developer-token")
+            .with_user_token("user-token");
+        client.get::<Value>("/v1/me/storefront", &[]).await.unwrap();
+        let head = server.request().await;
+        assert!(
+            head.contains("authorization: Bearer developer-token"),
+            "{head}"
+        );
+        assert!(head.contains("music-user-token: user-token"), "{head}");
+
+        let client = server.client("developer-token").with_user_token("");
+        client.get::<Value>("/v1/me/storefront", &[]).await.unwrap();
+        let head = server.request().await;
+        assert!(
+            head.contains("authorization: Bearer developer-token"),
+            "{head}"
+        );
+        assert!(!head.contains("music-user-token"), "{head}");
+    }
+
+    #[test]
+    fn invalid_base_urls_are_configuration_errors() {
+        let error = AppleMusicClient::new("token")
+            .with_base_url("not a url")
+            .unwrap_err();
+        assert!(matches!(error, Error::InvalidConfig(_)), "{error:?}");
+        assert_eq!(error.code(), ErrorCode::Configuration);
+    }
+
+    #[tokio::test]
+    async fn catalog_requests_use_the_storefront() {
+        let mut server = StandIn::start(vec![ok(json!({})), ok(json!({}))]).await;
+        let client = server.client("token").with_storefront("gb");
+
+        let path = client.catalog_path(None, "songs");
+        client
+            .get::<Value>(&path, &[("ids", "1".to_string())])
+            .await
+            .unwrap();
+        assert_eq!(
+            server.request_line().await,
+            "GET /v1/catalog/gb/songs?ids=1 HTTP/1.1"
+        );
+
+        let path = client.catalog_path(Some("jp"), "/albums/2");
+        client.get::<Value>(&path, &[]).await.unwrap();
+        assert_eq!(
+            server.request_line().await,
+            "GET /v1/catalog/jp/albums/2 HTTP/1.1"
+        );
+    }
+
+    #[tokio::test]
+    async fn get_all_follows_next_until_the_last_page() {
+        let mut server = StandIn::start(vec![
+            ok(json!({ "data": [1, 2], "next": "/v1/items?offset=2" })),
+            ok(json!({ "data": [3, 4], "next": "/v1/items?offset=4" })),
+            ok(json!({ "data": [5] })),
+        ])
+        .await;
+        let client = server.client("token");
+
+        let items: Vec<u32> = client.get_all("/v1/items", &[], None).await.unwrap();
+        assert_eq!(items, [1, 2, 3, 4, 5]);
+        assert_eq!(server.request_line().await, "GET /v1/items HTTP/1.1");
+        assert_eq!(
+            server.request_line().await,
+            "GET /v1/items?offset=2 HTTP/1.1"
+        );
+        assert_eq!(
+            server.request_line().await,
+            "GET /v1/items?offset=4 HTTP/1.1"
+        );
+    }
+
+    #[tokio::test]
+    async fn collect_pages_stops_at_max_items() {
+        // Only two pages are served, so requesting a third one fails the test.
+        let server = StandIn::start(vec![
+            ok(json!({ "data": [3, 4], "next": "/v1/items?offset=4" })),
+            ok(json!({ "data": [5, 6], "next": "/v1/items?offset=6" })),
+        ])
+        .await;
+        let client = server.client("token");
+        let first = Page {
+            data: vec![1, 2],
+            next: Some("/v1/items?offset=2".to_string()),
+            meta: None,
+        };
+
+        let items: Vec<u32> = client.collect_pages(first, Some(5)).await.unwrap();
+        assert_eq!(items, [1, 2, 3, 4, 5]);
+    }
+
+    #[tokio::test]
+    async fn error_bodies_become_api_errors() {
+        let server = StandIn::start(vec![
+            (
+                404,
+                json!({ "errors": [{ "title": "Resource Not Found", "detail": "No song 1" }] })
+                    .to_string(),
+            ),
+            (
+                401,
+                json!({ "errors": [{ "title": "Unauthorized" }] }).to_string(),
+            ),
+            (500, "upstream failure".to_string()),
+        ])
+        .await;
+        let client = server.client("token");
+
+        let error = client.get::<Value>("/v1/songs/1", &[]).await.unwrap_err();
+        assert!(
+            matches!(&error, Error::Api { status: 404, message } if message == "No song 1"),
+            "{error:?}"
+        );
+        let error = client.get::<Value>("/v1/songs/1", &[]).await.unwrap_err();
+        assert!(
+            matches!(&error, Error::Api { status: 401, message } if message == "Unauthorized"),
+            "{error:?}"
+        );
+        let error = client.get::<Value>("/v1/songs/1", &[]).await.unwrap_err();
+        assert!(
+            matches!(
+                &error,
+                Error::Api { status: 500, message } if message == "500 Internal Server Error"
+            ),
+            "{error:?}"
+        );
+    }
+}
@@ -0,0 +1,182 @@
+//! Typed Apple Music API resources.
+
+use serde::{Deserialize, Serialize};
+
+use crate::{models::MusicKitTrack, Artwork};
+
+/// One page of a resource collection, with the cursor to the following page.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Page<T> {
+    #[serde(default = "Vec::new")]
+    pub data: Vec<T>,
+    pub next: Option<String>,
+    pub meta: Option<serde_json::Value>,
+}
+
+/// A single Apple Music resource object.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Resource<A> {
+    pub id: String,
+    #[serde(rename = "type")]
+    pub kind: String,
+    pub href: Option<String>,
+    pub attributes: Option<A>,
+    pub relationships: Option<serde_json::Value>,
+}
+
+/// Artwork as returned by the API, now shared with the track model as [`Artwork`].
+pub type ArtworkAttributes = Artwork;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PlayParameters {
+    pub id: String,
+    pub kind: String,
+    /// The catalog identifier of a library resource.
+    pub catalog_id: Option<String>,
+    #[serde(default)]
+    pub is_library: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SongAttributes {
+    pub name: String,
+    pub artist_name: String,
+    pub album_name: Option<String>,
+    #[serde(default)]
+    pub genre_names: Vec<String>,
+    pub duration_in_millis: Option<u64>,
+    pub artwork: Option<Artwork>,
+    pub content_rating: Option<String>,
+    pub isrc: Option<String>,
+    pub track_number: Option<u32>,
+    pub disc_number: Option<u32>,
+    pub release_date: Option<String>,
+    pub composer_name: Option<String>,
+    pub url: Option<String>,
+    pub play_params: Option<PlayParameters>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AlbumAttributes {
+    pub name: String,
+    pub artist_name: String,
+    #[serde(default)]
+    pub genre_names: Vec<String>,
+    pub artwork: Option<Artwork>,
+    pub content_rating: Option<String>,
+    pub release_date: Option<String>,
+    pub track_count: Option<u32>,
+    pub record_label: Option<String>,
+    pub copyright: Option<String>,
+    pub upc: Option<String>,
+    #[serde(default)]
+    pub is_single: bool,
+    #[serde(default)]
+    pub is_complete: bool,
+    pub url: Option<String>,
+    pub play_params: Option<PlayParameters>,
+    /// Extended attributes requested with `extend`, and any others not modeled here.
+    #[serde(flatten)]
+    pub extra: serde_json::Map<String, serde_json::Value>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ArtistAttributes {
+    pub name: String,
+    #[serde(default)]
+    pub genre_names: Vec<String>,
+    pub artwork: Option<Artwork>,
+    pub url: Option<String>,
+    /// Extended attributes requested with `extend`, and any others not modeled here.
+    #[serde(flatten)]
+    pub extra: serde_json::Map<String, serde_json::Value>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PlaylistAttributes {
+    pub name: String,
+    pub curator_name: Option<String>,
+    pub playlist_type: Option<String>,
+    pub artwork: Option<Artwork>,
+    pub last_modified_date: Option<String>,
+    pub url: Option<String>,
+    pub play_params: Option<PlayParameters>,
+    /// Extended attributes requested with `extend`, and any others not modeled here.
+    #[serde(flatten)]
+    pub extra: serde_json::Map<String, serde_json::Value>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct StationAttributes {
+    pub name: String,
+    pub artwork: Option<Artwork>,
+    #[serde(default)]
+    pub is_live: bool,
+    pub duration_in_millis: Option<u64>,
+    pub url: Option<String>,
+    pub play_params: Option<PlayParameters>,
+}
+
+pub type Song = Resource<SongAttributes>;
+pub type Album = Resource<AlbumAttributes>;
+pub type Artist = Resource<ArtistAttributes>;
+pub type Playlist = Resource<PlaylistAttributes>;
+pub type Station = Resource<StationAttributes>;
+
+impl From<&Song> for MusicKitTrack {
+    fn from(song: &Song) -> Self {
+        let is_library = song.kind.starts_with("library-");
+        let (catalog_id, library_id) = if is_library {
+            let catalog_id = song
+                .attributes
+                .as_ref()
+                .and_then(|attributes| attributes.play_params.as_ref())
+                .and_then(|params| params.catalog_id.clone());
+            (catalog_id, Some(song.id.clone()))
+        } else {
+            (Some(song.id.clone()), None)
+        };
+        let Some(attributes) = &song.attributes else {
+            return MusicKitTrack {
+                id: song.id.clone(),
+                catalog_id,
+                library_id,
+                ..Default::default()
+            };
+        };
+        MusicKitTrack {
+            id: song.id.clone(),
+            catalog_id,
+            library_id,
+            title: attributes.name.clone(),
+            artist: attributes.artist_name.clone(),
+            album: attributes.album_name.clone().unwrap_or_default(),
+            genres: attributes.genre_names.clone(),
+            duration: attributes.duration_in_millis.unwrap_or_default() as f64 / 1000.0,
+            artwork: attributes.artwork.clone(),
+            is_explicit: attributes.content_rating.as_deref() == Some("explicit"),
+            is_playable: attributes.play_params.is_some(),
+            content_rating: attributes.content_rating.clone(),
+            isrc: attributes.isrc.clone(),
+            track_number: attributes.track_number,
+            disc_number: attributes.disc_number,
+            release_date: attributes.release_date.clone(),
+            composer: attributes.composer_name.clone(),
+            extra: Default::default(),
+        }
+    }
+}
+
+impl From<Song> for MusicKitTrack {
+    fn from(song: Song) -> Self {
+        MusicKitTrack::from(&song)
+    }
+}
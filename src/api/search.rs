@@ -0,0 +1,244 @@
+//! Catalog search.
+
+use serde::{Deserialize, Serialize};
+
+use super::{Album, AppleMusicClient, Artist, Page, Playlist, Song, Station};
+use crate::{models::MusicKitTrack, Result};
+
+/// A resource type that catalog search can return.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum SearchType {
+    Songs,
+    Albums,
+    Artists,
+    Playlists,
+    Stations,
+}
+
+impl SearchType {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            SearchType::Songs => "songs",
+            SearchType::Albums => "albums",
+            SearchType::Artists => "artists",
+            SearchType::Playlists => "playlists",
+            SearchType::Stations => "stations",
+        }
+    }
+}
+
+/// Parameters of a catalog search.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CatalogSearchRequest {
+    pub term: String,
+    /// The groups to search. Searches songs when empty.
+    #[serde(default)]
+    pub types: Vec<SearchType>,
+    pub limit: Option<u32>,
+    pub offset: Option<u32>,
+    /// Searches this storefront instead of the client's.
+    pub storefront: Option<String>,
+}
+
+impl CatalogSearchRequest {
+    pub fn new(term: impl Into<String>) -> Self {
+        Self {
+            term: term.into(),
+            ..Default::default()
+        }
+    }
+}
+
+/// Search results, one page per requested type that had matches.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CatalogSearchResults {
+    pub songs: Option<Page<Song>>,
+    pub albums: Option<Page<Album>>,
+    pub artists: Option<Page<Artist>>,
+    pub playlists: Option<Page<Playlist>>,
+    pub stations: Option<Page<Station>>,
+}
+
+impl CatalogSearchResults {
+    /// The matching songs as queueable tracks.
+    pub fn tracks(&self) -> Vec<MusicKitTrack> {
+        self.songs
+            .iter()
+            .flat_map(|page| &page.data)
+            .map(MusicKitTrack::from)
+            .collect()
+    }
+}
+
+#[derive(Deserialize)]
+struct SearchResponse {
+    #[serde(default)]
+    results: CatalogSearchResults,
+}
+
+impl AppleMusicClient {
+    /// Searches the catalog for `request.term` across the requested types.
+    pub async fn search_catalog(
+        &self,
+        request: &CatalogSearchRequest,
+    ) -> Result<CatalogSearchResults> {
+        if request.term.trim().is_empty() {
+            return Ok(CatalogSearchResults::default());
+        }
+        let types = if request.types.is_empty() {
+            SearchType::Songs.as_str().to_string()
+        } else {
+            request
+                .types
+                .iter()
+                .map(SearchType::as_str)
+                .collect::<Vec<_>>()
+                .join(",")
+        };
+        let mut query = vec![("term", request.term.clone()), ("types", types)];
+        if let Some(limit) = request.limit {
+            query.push(("limit", limit.to_string()));
+        }
+        if let Some(offset) = request.offset {
+            query.push(("offset", offset.to_string()));
+        }
+        let path = self.catalog_path(request.storefront.as_deref(), "search");
+        let response: SearchResponse = self.get(&path, &query).await?;
+        Ok(response.results)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::{json, Value};
+
+    use super::*;
+    use crate::api::stand_in::StandIn;
+
+    fn ok(body: Value) -> (u16, String) {
+        (200, body.to_string())
+    }
+
+    fn resource(id: &str, kind: &str, attributes: Value) -> Value {
+        json!({ "id": id, "type": kind, "attributes": attributes })
+    }
+
+    #[tokio::test]
+    async fn searches_songs_in_the_client_storefront_by_default() {
+        let mut server = StandIn::start(vec![ok(json!({ "results": {} }))]).await;
+        let client = server.client("token");
+
+        let results = client
+            .search_catalog(&CatalogSearchRequest::new("daft punk"))
+            .await
+            .unwrap();
+        assert_eq!(
+            server.request_line().await,
+            "GET /v1/catalog/us/search?term=daft+punk&types=songs HTTP/1.1"
+        );
+        assert!(results.songs.is_none());
+        assert!(results.tracks().is_empty());
+    }
+
+    #[tokio::test]
+    async fn types_paging_and_storefront_are_sent() {
+        let mut server = StandIn::start(vec![ok(json!({ "results": {} }))]).await;
+        let client = server.client("token");
+        let request = CatalogSearchRequest {
+            types: vec![
+                SearchType::Albums,
+                SearchType::Artists,
+                SearchType::Stations,
+            ],
+            limit: Some(5),
+            offset: Some(10),
+            storefront: Some("jp".to_string()),
+            ..CatalogSearchRequest::new("daft")
+        };
+
+        client.search_catalog(&request).await.unwrap();
+        assert_eq!(
+            server.request_line().await,
+            concat!(
+                "GET /v1/catalog/jp/search?term=daft&types=albums%2Cartists%2Cstations",
+                "&limit=5&offset=10 HTTP/1.1",
+            )
+        );
+    }
+
+    #[tokio::test]
+    async fn blank_terms_are_not_searched() {
+        // No responses are served, so a request would fail the test.
+        let server = StandIn::start(vec![]).await;
+        let client = server.client("token");
+
+        let results = client
+            .search_catalog(&CatalogSearchRequest::new("  "))
+            .await
+            .unwrap();
+        assert!(results.songs.is_none());
+    }
+
+    #[tokio::test]
+    async fn results_are_grouped_by_type_and_songs_become_tracks() {
+        let server = StandIn::start(vec![ok(json!({ "results": {
+            "songs": {
+                "data": [resource("1", "songs", json!({
+                    "name": "One More Time",
+                    "artistName": "Daft Punk",
+                    "albumName": "Discovery",
+                    "genreNames": ["Electronic"],
+                    "durationInMillis": 320357,
+                    "artwork": {
+                        "url": "https://example.com/{w}x{h}bb.jpg",
+                        "width": 3000,
+                        "height": 3000,
+                    },
+                    "playParams": { "id": "1", "kind": "song" },
+                }))],
+                "next": "/v1/catalog/us/search?offset=1&term=daft&types=songs",
+            },
+            "albums": { "data": [resource("2", "albums", json!({
+                "name": "Discovery",
+                "artistName": "Daft Punk",
+            }))] },
+            "stations": { "data": [resource("ra.3", "stations", json!({
+                "name": "Daft Punk Radio",
+                "isLive": false,
+            }))] },
+        } }))])
+        .await;
+        let client = server.client("token");
+
+        let results = client
+            .search_catalog(&CatalogSearchRequest::new("daft"))
+            .await
+            .unwrap();
+        let songs = results.songs.as_ref().unwrap();
+        assert!(songs.next.is_some());
+        assert_eq!(results.albums.as_ref().unwrap().data[0].id, "2");
+        assert_eq!(results.stations.as_ref().unwrap().data[0].id, "ra.3");
+        assert!(results.artists.is_none());
+        assert!(results.playlists.is_none());
+
+        let tracks = results.tracks();
+        assert_eq!(tracks.len(), 1);
+        let track = &tracks[0];
+        assert_eq!(track.id, "1");
+        assert_eq!(track.catalog_id.as_deref(), Some("1"));
+        assert_eq!(track.title, "One More Time");
+        assert_eq!(track.artist, "Daft Punk");
+        assert_eq!(track.album, "Discovery");
+        assert_eq!(track.genres, ["Electronic"]);
+        assert_eq!(track.duration_in_millis(), 320357);
+        assert_eq!(
+            track.artwork_url(100, 100).as_deref(),
+            Some("https://example.com/100x100bb.jpg")
+        );
+        assert!(track.is_playable);
+        assert!(!track.is_explicit);
+    }
+}
@@ -0,0 +1,77 @@
+//! A local stand-in for the Apple Music API, for exercising the client in tests.
+
+use tokio::{
+    io::{AsyncReadExt, AsyncWriteExt},
+    net::{TcpListener, TcpStream},
+    sync::mpsc,
+};
+
+use super::AppleMusicClient;
+
+/// Answers each request with the next canned response and records the request heads.
+pub(crate) struct StandIn {
+    url: String,
+    requests: mpsc::UnboundedReceiver<String>,
+}
+
+impl StandIn {
+    /// Serves `responses`, given as status and JSON body, one per request in order.
+    pub(crate) async fn start(responses: Vec<(u16, String)>) -> Self {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap());
+        let (sender, requests) = mpsc::unbounded_channel();
+        tokio::spawn(async move {
+            for (status, body) in responses {
+                let (mut socket, _) = listener.accept().await.unwrap();
+                let _ = sender.send(read_head(&mut socket).await);
+                let response = format!(
+                    "HTTP/1.1 {status} Stand-In\r\ncontent-type: application/json\r\n\
+                     content-length: {}\r\nconnection: close\r\n\r\n{body}",
+                    body.len()
+                );
+                socket.write_all(response.as_bytes()).await.unwrap();
+            }
+        });
+        Self { url, requests }
+    }
+
+    /// A client pointed at the stand-in.
+    pub(crate) fn client(&self, developer_token: &str) -> AppleMusicClient {
+        AppleMusicClient::new(developer_token)
+            .with_base_url(&self.url)
+            .unwrap()
+    }
+
+    /// The head of the next request, with header names in lowercase.
+    pub(crate) async fn request(&mut self) -> String {
+        self.requests.recv().await.unwrap()
+    }
+
+    /// The request line of the next request, e.g. `GET /v1/catalog/us/songs HTTP/1.1`.
+    pub(crate) async fn request_line(&mut self) -> String {
+        let head = self.request().await;
+        head.lines().next().unwrap_or_default().to_string()
+    }
+}
+
+async fn read_head(socket: &mut TcpStream) -> String {
+    let mut head = Vec::new();
+    let mut buffer = [0; 1024];
+    while !head.windows(4).any(|window| window == b"\r\n\r\n") {
+        let read = socket.read(&mut buffer).await.unwrap();
+        if read == 0 {
+            break;
+        }
+        head.extend_from_slice(&buffer[..read]);
+    }
+    let head = String::from_utf8_lossy(&head);
+    let mut lines = head.lines();
+    let request_line = lines.next().unwrap_or_default().to_string();
+    std::iter::once(request_line)
+        .chain(lines.map(|line| match line.split_once(':') {
+            Some((name, value)) => format!("{}:{value}", name.to_ascii_lowercase()),
+            None => line.to_string(),
+        }))
+        .collect::<Vec<_>>()
+        .join("\r\n")
+}
@@ -0,0 +1,396 @@
+//! Search suggestions and hints for type-ahead search boxes.
+
+use std::{future::Future, time::Duration};
+
+use serde::{de::Error as _, Deserialize, Deserializer, Serialize};
+use tokio::sync::watch;
+
+use super::{Album, AppleMusicClient, Artist, Playlist, SearchType, Song, Station};
+use crate::Result;
+
+/// How long [`SuggestionDebouncer`] waits for typing to settle by default.
+pub const DEFAULT_SUGGESTION_DEBOUNCE: Duration = Duration::from_millis(250);
+
+/// A kind of suggestion the search-suggestions endpoint can return.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum SuggestionKind {
+    Terms,
+    TopResults,
+}
+
+impl SuggestionKind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            SuggestionKind::Terms => "terms",
+            SuggestionKind::TopResults => "topResults",
+        }
+    }
+}
+
+/// Parameters of a search-suggestions request.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SearchSuggestionsRequest {
+    pub term: String,
+    /// The suggestion kinds to return. Returns both when empty.
+    #[serde(default)]
+    pub kinds: Vec<SuggestionKind>,
+    /// The resource types for top results. Uses every searchable type when empty.
+    #[serde(default)]
+    pub types: Vec<SearchType>,
+    pub limit: Option<u32>,
+    pub storefront: Option<String>,
+}
+
+/// A completed search term to offer the user.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TermSuggestion {
+    pub search_term: String,
+    pub display_term: String,
+}
+
+/// A catalog resource suggested as a top result.
+#[derive(Debug, Clone, Serialize)]
+#[serde(untagged)]
+pub enum TopResult {
+    Song(Song),
+    Album(Album),
+    Artist(Artist),
+    Playlist(Playlist),
+    Station(Station),
+    /// A resource type this crate does not model yet.
+    Other(serde_json::Value),
+}
+
+impl<'de> Deserialize<'de> for TopResult {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> std::result::Result<Self, D::Error> {
+        let value = serde_json::Value::deserialize(deserializer)?;
+        let kind = value.get("type").and_then(|kind| kind.as_str());
+        let result = match kind {
+            Some("songs") => serde_json::from_value(value).map(TopResult::Song),
+            Some("albums") => serde_json::from_value(value).map(TopResult::Album),
+            Some("artists") => serde_json::from_value(value).map(TopResult::Artist),
+            Some("playlists") => serde_json::from_value(value).map(TopResult::Playlist),
+            Some("stations") => serde_json::from_value(value).map(TopResult::Station),
+            _ => Ok(TopResult::Other(value)),
+        };
+        result.map_err(D::Error::custom)
+    }
+}
+
+/// Term suggestions and top results for a partial search term.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SearchSuggestions {
+    pub terms: Vec<TermSuggestion>,
+    pub top_results: Vec<TopResult>,
+}
+
+#[derive(Deserialize)]
+struct SuggestionsResponse {
+    #[serde(default)]
+    results: SuggestionsResults,
+}
+
+#[derive(Default, Deserialize)]
+struct SuggestionsResults {
+    #[serde(default)]
+    suggestions: Vec<RawSuggestion>,
+}
+
+#[derive(Deserialize)]
+#[serde(tag = "kind", rename_all = "camelCase")]
+enum RawSuggestion {
+    #[serde(rename_all = "camelCase")]
+    Terms {
+        search_term: String,
+        display_term: String,
+    },
+    TopResults {
+        content: Box<TopResult>,
+    },
+}
+
+#[derive(Deserialize)]
+struct HintsResponse {
+    #[serde(default)]
+    results: HintsResults,
+}
+
+#[derive(Default, Deserialize)]
+struct HintsResults {
+    #[serde(default)]
+    terms: Vec<String>,
+}
+
+impl AppleMusicClient {
+    /// Fetches term suggestions and top results for a partial search term.
+    ///
+    /// When terms were asked for but the suggestions endpoint returned none, the
+    /// completions from [`AppleMusicClient::search_hints`] are offered instead.
+    pub async fn search_suggestions(
+        &self,
+        request: &SearchSuggestionsRequest,
+    ) -> Result<SearchSuggestions> {
+        if request.term.trim().is_empty() {
+            return Ok(SearchSuggestions::default());
+        }
+        let kinds = if request.kinds.is_empty() {
+            &[SuggestionKind::Terms, SuggestionKind::TopResults][..]
+        } else {
+            &request.kinds[..]
+        };
+        let types = if request.types.is_empty() {
+            &[
+                SearchType::Songs,
+                SearchType::Albums,
+                SearchType::Artists,
+                SearchType::Playlists,
+                SearchType::Stations,
+            ][..]
+        } else {
+            &request.types[..]
+        };
+        let mut query = vec![
+            ("term", request.term.clone()),
+            ("kinds", join(kinds.iter().map(SuggestionKind::as_str))),
+        ];
+        if kinds.contains(&SuggestionKind::TopResults) {
+            query.push(("types", join(types.iter().map(SearchType::as_str))));
+        }
+        if let Some(limit) = request.limit {
+            query.push(("limit", limit.to_string()));
+        }
+        let path = self.catalog_path(request.storefront.as_deref(), "search/suggestions");
+        let response: SuggestionsResponse = self.get(&path, &query).await?;
+
+        let mut suggestions = SearchSuggestions::default();
+        for suggestion in response.results.suggestions {
+            match suggestion {
+                RawSuggestion::Terms {
+                    search_term,
+                    display_term,
+                } => suggestions.terms.push(TermSuggestion {
+                    search_term,
+                    display_term,
+                }),
+                RawSuggestion::TopResults { content } => suggestions.top_results.push(*content),
+            }
+        }
+        if suggestions.terms.is_empty() && kinds.contains(&SuggestionKind::Terms) {
+            let hints = self
+                .search_hints(&request.term, request.limit, request.storefront.as_deref())
+                .await?;
+            suggestions.terms = hints
+                .into_iter()
+                .map(|term| TermSuggestion {
+                    search_term: term.clone(),
+                    display_term: term,
+                })
+                .collect();
+        }
+        Ok(suggestions)
+    }
+
+    /// Fetches plain search-term completions from the hints endpoint.
+    pub async fn search_hints(
+        &self,
+        term: &str,
+        limit: Option<u32>,
+        storefront: Option<&str>,
+    ) -> Result<Vec<String>> {
+        if term.trim().is_empty() {
+            return Ok(Vec::new());
+        }
+        let mut query = vec![("term", term.to_string())];
+        if let Some(limit) = limit {
+            query.push(("limit", limit.to_string()));
+        }
+        let path = self.catalog_path(storefront, "search/hints");
+        let response: HintsResponse = self.get(&path, &query).await?;
+        Ok(response.results.terms)
+    }
+}
+
+fn join<'a>(values: impl Iterator<Item = &'a str>) -> String {
+    values.collect::<Vec<_>>().join(",")
+}
+
+/// Debounces type-ahead requests and cancels the ones a newer call supersedes.
+///
+/// Every call to [`SuggestionDebouncer::run`] waits for the debounce delay before
+/// starting its request. When another call arrives in the meantime, or while the
+/// request is in flight, the older call is dropped and resolves to `None`.
+#[derive(Debug)]
+pub struct SuggestionDebouncer {
+    delay: Duration,
+    generation: watch::Sender<u64>,
+}
+
+impl Default for SuggestionDebouncer {
+    fn default() -> Self {
+        Self::new(DEFAULT_SUGGESTION_DEBOUNCE)
+    }
+}
+
+impl SuggestionDebouncer {
+    pub fn new(delay: Duration) -> Self {
+        Self {
+            delay,
+            generation: watch::channel(0).0,
+        }
+    }
+
+    pub fn delay(&self) -> Duration {
+        self.delay
+    }
+
+    /// Runs `request` once the debounce delay passed without a newer call.
+    pub async fn run<F: Future>(&self, request: F) -> Option<F::Output> {
+        let mut updates = self.generation.subscribe();
+        let mut generation = 0;
+        self.generation.send_modify(|current| {
+            *current += 1;
+            generation = *current;
+        });
+        let superseded = async {
+            loop {
+                if updates.changed().await.is_err() {
+                    std::future::pending::<()>().await;
+                }
+                if *updates.borrow_and_update() != generation {
+                    break;
+                }
+            }
+        };
+        let debounced = async {
+            tokio::time::sleep(self.delay).await;
+            request.await
+        };
+        tokio::select! {
+            _ = superseded => None,
+            output = debounced => Some(output),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use serde_json::json;
+
+    use super::*;
+    use crate::api::stand_in::StandIn;
+
+    fn request(kinds: Vec<SuggestionKind>) -> SearchSuggestionsRequest {
+        SearchSuggestionsRequest {
+            term: "beat".to_string(),
+            kinds,
+            ..Default::default()
+        }
+    }
+
+    #[tokio::test]
+    async fn suggested_terms_are_returned_without_asking_for_hints() {
+        // A request to the hints endpoint would find no response left and fail.
+        let mut server = StandIn::start(vec![(
+            200,
+            json!({ "results": { "suggestions": [
+                { "kind": "terms", "searchTerm": "beatles", "displayTerm": "Beatles" }
+            ] } })
+            .to_string(),
+        )])
+        .await;
+        let client = server.client("token");
+
+        let suggestions = client
+            .search_suggestions(&request(vec![SuggestionKind::Terms]))
+            .await
+            .unwrap();
+        assert_eq!(suggestions.terms.len(), 1);
+        assert_eq!(suggestions.terms[0].search_term, "beatles");
+        assert_eq!(suggestions.terms[0].display_term, "Beatles");
+        assert_eq!(
+            server.request_line().await,
+            "GET /v1/catalog/us/search/suggestions?term=beat&kinds=terms HTTP/1.1"
+        );
+    }
+
+    #[tokio::test]
+    async fn hints_fill_in_when_no_terms_are_suggested() {
+        let mut server = StandIn::start(vec![
+            (200, json!({ "results": {} }).to_string()),
+            (
+                200,
+                json!({ "results": { "terms": ["beatles", "beat it"] } }).to_string(),
+            ),
+        ])
+        .await;
+        let client = server.client("token");
+
+        let suggestions = client.search_suggestions(&request(vec![])).await.unwrap();
+        let terms: Vec<_> = suggestions
+            .terms
+            .iter()
+            .map(|term| (term.search_term.as_str(), term.display_term.as_str()))
+            .collect();
+        assert_eq!(terms, [("beatles", "beatles"), ("beat it", "beat it")]);
+        server.request().await;
+        assert_eq!(
+            server.request_line().await,
+            "GET /v1/catalog/us/search/hints?term=beat HTTP/1.1"
+        );
+    }
+
+    #[tokio::test]
+    async fn hints_are_not_fetched_for_top_results_only() {
+        let server = StandIn::start(vec![(200, json!({ "results": {} }).to_string())]).await;
+        let client = server.client("token");
+
+        let suggestions = client
+            .search_suggestions(&request(vec![SuggestionKind::TopResults]))
+            .await
+            .unwrap();
+        assert!(suggestions.terms.is_empty());
+        assert!(suggestions.top_results.is_empty());
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn a_single_call_runs_after_the_delay() {
+        let debouncer = SuggestionDebouncer::new(Duration::from_millis(250));
+        let started = tokio::time::Instant::now();
+        assert_eq!(debouncer.run(async { 1 }).await, Some(1));
+        assert!(started.elapsed() >= Duration::from_millis(250));
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn a_call_superseded_while_debouncing_resolves_to_none() {
+        let debouncer = SuggestionDebouncer::new(Duration::from_millis(250));
+        let later = async {
+            tokio::time::sleep(Duration::from_millis(100)).await;
+            debouncer.run(async { 2 }).await
+        };
+        let (first, second) = tokio::join!(debouncer.run(async { 1 }), later);
+        assert_eq!(first, None);
+        assert_eq!(second, Some(2));
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn a_call_superseded_while_in_flight_resolves_to_none() {
+        let debouncer = SuggestionDebouncer::new(Duration::from_millis(250));
+        let slow_request = async {
+            tokio::time::sleep(Duration::from_secs(5)).await;
+            1
+        };
+        let later = async {
+            tokio::time::sleep(Duration::from_secs(1)).await;
+            debouncer.run(async { 2 }).await
+        };
+        let (first, second) = tokio::join!(debouncer.run(slow_request), later);
+        assert_eq!(first, None);
+        assert_eq!(second, Some(2));
+    }
+}
@@ -0,0 +1,246 @@
+//! Resolving Apple Music artwork URLs.
+//!
+//! Artwork URLs from Apple Music are templates: `{w}` and `{h}` stand for the size
+//! to render, and some carry `{c}` for the crop and `{f}` for the image format.
+
+use serde::{Deserialize, Serialize};
+
+/// The largest edge Apple serves artwork at when the artwork does not report its own
+/// size.
+pub const MAX_ARTWORK_SIZE: u32 = 3000;
+
+/// An image format the artwork can be rendered in.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ArtworkFormat {
+    #[default]
+    Jpg,
+    Png,
+    Webp,
+}
+
+impl ArtworkFormat {
+    pub fn extension(self) -> &'static str {
+        match self {
+            ArtworkFormat::Jpg => "jpg",
+            ArtworkFormat::Png => "png",
+            ArtworkFormat::Webp => "webp",
+        }
+    }
+}
+
+/// Artwork for a track, album, artist, playlist or station.
+///
+/// The `url` field is kept as reported, placeholders included; [`Artwork::url`]
+/// resolves it for a size. The colors are hex strings without a leading `#` and are
+/// only reported for catalog artwork.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Artwork {
+    pub url: String,
+    /// The largest width the artwork is available in.
+    pub width: Option<u32>,
+    /// The largest height the artwork is available in.
+    pub height: Option<u32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub bg_color: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub text_color1: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub text_color2: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub text_color3: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub text_color4: Option<String>,
+}
+
+impl Artwork {
+    /// Artwork for a URL template of unknown size and colors.
+    pub fn new(url: impl Into<String>) -> Self {
+        Self {
+            url: url.into(),
+            ..Default::default()
+        }
+    }
+
+    /// Whether the URL has a size to fill in.
+    pub fn is_template(&self) -> bool {
+        self.url.contains("{w}") || self.url.contains("{h}")
+    }
+
+    /// The URL of the artwork rendered at `width` x `height` pixels in `format`.
+    ///
+    /// URLs without placeholders are returned unchanged.
+    pub fn url(&self, width: u32, height: u32, format: ArtworkFormat) -> String {
+        let url = self
+            .url
+            .replace("{w}", &width.to_string())
+            .replace("{h}", &height.to_string())
+            .replace("{c}", "bb");
+        if url.contains("{f}") {
+            return url.replace("{f}", format.extension());
+        }
+        if !self.is_template() {
+            return url;
+        }
+        match url.rsplit_once('.') {
+            Some((base, extension))
+                if ["jpg", "jpeg", "png", "webp"]
+                    .contains(&extension.to_ascii_lowercase().as_str()) =>
+            {
+                format!("{base}.{}", format.extension())
+            }
+            _ => url,
+        }
+    }
+
+    /// The pixel size to request for a `width` x `height` box in CSS pixels on a
+    /// display with `device_pixel_ratio`.
+    ///
+    /// The size is rounded up and scaled down, keeping its aspect ratio, to the
+    /// largest size the artwork is available in.
+    pub fn size_for(&self, width: f64, height: f64, device_pixel_ratio: f64) -> (u32, u32) {
+        let ratio = if device_pixel_ratio.is_finite() && device_pixel_ratio > 0.0 {
+            device_pixel_ratio
+        } else {
+            1.0
+        };
+        let width = (width.max(1.0) * ratio).ceil();
+        let height = (height.max(1.0) * ratio).ceil();
+        let max_width = f64::from(self.width.unwrap_or(MAX_ARTWORK_SIZE).max(1));
+        let max_height = f64::from(self.height.unwrap_or(MAX_ARTWORK_SIZE).max(1));
+        let scale = (max_width / width).min(max_height / height).min(1.0);
+        (
+            ((width * scale).round() as u32).max(1),
+            ((height * scale).round() as u32).max(1),
+        )
+    }
+
+    /// The URL for a `width` x `height` box in CSS pixels on a display with
+    /// `device_pixel_ratio`.
+    pub fn url_for(
+        &self,
+        width: f64,
+        height: f64,
+        device_pixel_ratio: f64,
+        format: ArtworkFormat,
+    ) -> String {
+        let (width, height) = self.size_for(width, height, device_pixel_ratio);
+        self.url(width, height, format)
+    }
+}
+
+impl From<String> for Artwork {
+    fn from(url: String) -> Self {
+        Self::new(url)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    const TEMPLATE: &str = "https://is1-ssl.mzstatic.com/image/thumb/a/{w}x{h}bb.jpg";
+
+    fn sized(width: u32, height: u32) -> Artwork {
+        Artwork {
+            width: Some(width),
+            height: Some(height),
+            ..Artwork::new(TEMPLATE)
+        }
+    }
+
+    #[test]
+    fn width_and_height_are_filled_in() {
+        let artwork = Artwork::new(TEMPLATE);
+        assert!(artwork.is_template());
+        assert_eq!(
+            artwork.url(300, 200, ArtworkFormat::Jpg),
+            "https://is1-ssl.mzstatic.com/image/thumb/a/300x200bb.jpg"
+        );
+        assert_eq!(
+            Artwork::new("https://example.com/{w}x{h}{c}.{f}").url(64, 64, ArtworkFormat::Png),
+            "https://example.com/64x64bb.png"
+        );
+    }
+
+    #[test]
+    fn the_format_replaces_the_placeholder_or_the_extension() {
+        let artwork = Artwork::new("https://example.com/{w}x{h}bb.{f}");
+        assert_eq!(
+            artwork.url(10, 10, ArtworkFormat::Webp),
+            "https://example.com/10x10bb.webp"
+        );
+        assert_eq!(
+            Artwork::new(TEMPLATE).url(10, 10, ArtworkFormat::Png),
+            "https://is1-ssl.mzstatic.com/image/thumb/a/10x10bb.png"
+        );
+        assert_eq!(
+            Artwork::new("https://example.com/{w}x{h}.JPEG").url(10, 10, ArtworkFormat::Webp),
+            "https://example.com/10x10.webp"
+        );
+        // An unknown extension is left alone, and so are URLs without placeholders.
+        assert_eq!(
+            Artwork::new("https://example.com/{w}x{h}.gif").url(10, 10, ArtworkFormat::Png),
+            "https://example.com/10x10.gif"
+        );
+        assert_eq!(
+            Artwork::new("https://example.com/cover.jpg").url(10, 10, ArtworkFormat::Png),
+            "https://example.com/cover.jpg"
+        );
+    }
+
+    #[test]
+    fn sizes_are_scaled_by_the_device_pixel_ratio_and_rounded_up() {
+        let artwork = sized(3000, 3000);
+        assert_eq!(artwork.size_for(100.0, 50.0, 2.0), (200, 100));
+        assert_eq!(artwork.size_for(100.5, 33.2, 1.0), (101, 34));
+        assert_eq!(artwork.size_for(100.0, 100.0, 1.5), (150, 150));
+        // Unusable ratios and sizes fall back to 1.
+        assert_eq!(artwork.size_for(100.0, 100.0, 0.0), (100, 100));
+        assert_eq!(artwork.size_for(100.0, 100.0, f64::NAN), (100, 100));
+        assert_eq!(artwork.size_for(0.0, -5.0, 1.0), (1, 1));
+    }
+
+    #[test]
+    fn sizes_are_clamped_to_the_source_keeping_the_aspect_ratio() {
+        let artwork = sized(600, 400);
+        assert_eq!(artwork.size_for(500.0, 500.0, 2.0), (400, 400));
+        assert_eq!(artwork.size_for(800.0, 400.0, 1.0), (600, 300));
+        assert_eq!(
+            Artwork::new(TEMPLATE).size_for(2000.0, 2000.0, 3.0),
+            (MAX_ARTWORK_SIZE, MAX_ARTWORK_SIZE)
+        );
+        assert_eq!(
+            artwork.url_for(500.0, 500.0, 2.0, ArtworkFormat::Jpg),
+            "https://is1-ssl.mzstatic.com/image/thumb/a/400x400bb.jpg"
+        );
+    }
+
+    #[test]
+    fn colors_are_kept_without_a_leading_hash() {
+        let artwork: Artwork = serde_json::from_value(json!({
+            "url": TEMPLATE,
+            "width": 1200,
+            "height": 1200,
+            "bgColor": "1a1b1c",
+            "textColor1": "fafafa",
+        }))
+        .unwrap();
+        assert_eq!(artwork.bg_color.as_deref(), Some("1a1b1c"));
+        assert_eq!(artwork.text_color1.as_deref(), Some("fafafa"));
+        assert_eq!(artwork.text_color2, None);
+        assert_eq!(
+            serde_json::to_value(&artwork).unwrap(),
+            json!({
+                "url": TEMPLATE,
+                "width": 1200,
+                "height": 1200,
+                "bgColor": "1a1b1c",
+                "textColor1": "fafafa",
+            })
+        );
+    }
+}
@@ -0,0 +1,603 @@
+//! A size-bounded disk cache for artwork, served through the `musickit-artwork` URI
+//! scheme.
+//!
+//! Webviews load `musickit-artwork://localhost/<percent-encoded artwork URL>` (see
+//! [`protocol_url`]). The first request downloads the image into the cache directory;
+//! later ones, including those made offline, are answered from disk. Requests for an
+//! image that is still downloading share that download. When the cache grows past its
+//! budget the least recently used images are deleted.
+
+use std::{
+    collections::{BTreeMap, HashMap},
+    io::ErrorKind,
+    path::{Path, PathBuf},
+    sync::{Arc, Mutex, PoisonError},
+    time::SystemTime,
+};
+
+use futures_util::future::{BoxFuture, FutureExt, Shared, TryFutureExt};
+use reqwest::Url;
+use tauri::{
+    http::{header, Request, Response, StatusCode},
+    AppHandle, Listener, Manager, Runtime, UriSchemeContext, UriSchemeResponder,
+};
+
+use crate::{events::*, models::*, Error, ErrorCode, MusicKit, MusicKitExt, Result};
+
+/// The URI scheme artwork is served through.
+pub const ARTWORK_SCHEME: &str = "musickit-artwork";
+
+/// How much disk space the cache may use when `artworkCache.maxSizeMb` is not set.
+pub const DEFAULT_ARTWORK_CACHE_SIZE: u64 = 200 * 1024 * 1024;
+
+/// How many upcoming queue items have their artwork prefetched by default.
+pub const DEFAULT_PREFETCH_COUNT: usize = 5;
+
+/// The edge, in pixels, prefetched artwork is rendered at by default.
+pub const DEFAULT_PREFETCH_SIZE: u32 = 600;
+
+/// Hosts artwork is downloaded from. Anything else is refused so the scheme cannot be
+/// used to fetch arbitrary URLs.
+const ARTWORK_HOSTS: [&str; 2] = ["mzstatic.com", "apple.com"];
+
+/// Suffix of images that are still being written.
+const PARTIAL_SUFFIX: &str = ".partial";
+
+/// The largest image that is downloaded, in bytes.
+const MAX_DOWNLOAD_SIZE: u64 = 16 * 1024 * 1024;
+
+/// A download of one image, awaited by every request for it.
+type Download = Shared<BoxFuture<'static, std::result::Result<Arc<Vec<u8>>, Arc<Error>>>>;
+
+/// Artwork images stored on disk, evicted least recently used first.
+#[derive(Debug)]
+pub struct ArtworkCache {
+    dir: PathBuf,
+    max_size: u64,
+    http: reqwest::Client,
+    index: Mutex<Index>,
+    downloads: Mutex<HashMap<String, Download>>,
+}
+
+/// Which images are on disk and in which order they were last used.
+#[derive(Debug, Default)]
+struct Index {
+    entries: HashMap<String, Entry>,
+    by_use: BTreeMap<u64, String>,
+    clock: u64,
+    size: u64,
+}
+
+#[derive(Debug, Clone, Copy)]
+struct Entry {
+    size: u64,
+    used: u64,
+}
+
+impl Index {
+    /// Marks `key` as used now, returning whether it is cached.
+    fn touch(&mut self, key: &str) -> bool {
+        let clock = self.clock + 1;
+        let Some(entry) = self.entries.get_mut(key) else {
+            return false;
+        };
+        self.by_use.remove(&entry.used);
+        entry.used = clock;
+        self.by_use.insert(clock, key.to_string());
+        self.clock = clock;
+        true
+    }
+
+    fn insert(&mut self, key: String, size: u64) {
+        self.remove(&key);
+        self.clock += 1;
+        self.size += size;
+        self.by_use.insert(self.clock, key.clone());
+        self.entries.insert(
+            key,
+            Entry {
+                size,
+                used: self.clock,
+            },
+        );
+    }
+
+    fn remove(&mut self, key: &str) -> Option<u64> {
+        let entry = self.entries.remove(key)?;
+        self.by_use.remove(&entry.used);
+        self.size -= entry.size;
+        Some(entry.size)
+    }
+
+    /// Removes least recently used entries until the total fits in `max_size`,
+    /// returning their keys.
+    fn evict_to(&mut self, max_size: u64) -> Vec<String> {
+        let mut evicted = Vec::new();
+        while self.size > max_size {
+            let Some((_, key)) = self.by_use.pop_first() else {
+                break;
+            };
+            if let Some(entry) = self.entries.remove(&key) {
+                self.size -= entry.size;
+            }
+            evicted.push(key);
+        }
+        evicted
+    }
+}
+
+impl ArtworkCache {
+    /// Opens the cache in `dir`, picking up the images already stored there.
+    pub fn open(dir: impl Into<PathBuf>, max_size: u64) -> Result<Self> {
+        let dir = dir.into();
+        std::fs::create_dir_all(&dir).map_err(io_error)?;
+        let mut stored = Vec::new();
+        for entry in std::fs::read_dir(&dir).map_err(io_error)? {
+            let entry = entry.map_err(io_error)?;
+            let name = entry.file_name().to_string_lossy().into_owned();
+            let Ok(metadata) = entry.metadata() else {
+                continue;
+            };
+            if !metadata.is_file() {
+                continue;
+            }
+            if name.ends_with(PARTIAL_SUFFIX) {
+                let _ = std::fs::remove_file(entry.path());
+                continue;
+            }
+            let used = metadata
+                .modified()
+                .or_else(|_| metadata.created())
+                .unwrap_or(SystemTime::UNIX_EPOCH);
+            stored.push((used, name, metadata.len()));
+        }
+        stored.sort();
+
+        let mut index = Index::default();
+        for (_, key, size) in stored {
+            index.insert(key, size);
+        }
+        let cache = Self {
+            dir,
+            max_size,
+            http: reqwest::Client::new(),
+            index: Mutex::new(index),
+            downloads: Default::default(),
+        };
+        cache.evict_over_budget();
+        Ok(cache)
+    }
+
+    /// Uses a preconfigured `reqwest` client for downloads.
+    pub fn with_http_client(mut self, http: reqwest::Client) -> Self {
+        self.http = http;
+        self
+    }
+
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    pub fn max_size(&self) -> u64 {
+        self.max_size
+    }
+
+    /// The disk space the cached images use, in bytes.
+    pub fn size(&self) -> u64 {
+        self.index().size
+    }
+
+    /// The number of cached images.
+    pub fn len(&self) -> usize {
+        self.index().entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Whether the image at `url` is cached.
+    pub fn contains(&self, url: &str) -> bool {
+        self.index().entries.contains_key(&cache_key(url))
+    }
+
+    /// Returns the image at `url`, downloading and caching it if needed.
+    ///
+    /// Concurrent requests for an image that is not cached yet share one download.
+    pub async fn get(&self, url: &str) -> Result<Vec<u8>> {
+        let key = cache_key(url);
+        let url = artwork_url(url)?;
+        if self.index().touch(&key) {
+            match tokio::fs::read(self.dir.join(&key)).await {
+                Ok(bytes) => return Ok(bytes),
+                // Deleted behind our back; download it again.
+                Err(_) => {
+                    self.index().remove(&key);
+                }
+            }
+        }
+        let download = self
+            .downloads()
+            .entry(key.clone())
+            .or_insert_with(|| self.download(&key, url))
+            .clone();
+        let result = download.clone().await;
+
+        // The first request to see the download finish records it, so a failed one is
+        // retried by the next request and a stored one is found in the index.
+        let first = {
+            let mut downloads = self.downloads();
+            let first = downloads
+                .get(&key)
+                .is_some_and(|pending| pending.ptr_eq(&download));
+            if first {
+                if let Ok(bytes) = &result {
+                    self.index().insert(key.clone(), bytes.len() as u64);
+                }
+                downloads.remove(&key);
+            }
+            first
+        };
+        if first {
+            self.evict_over_budget();
+        }
+        match result {
+            Ok(bytes) => Ok(bytes.to_vec()),
+            Err(error) => Err(download_error(&error)),
+        }
+    }
+
+    /// Downloads the images at `urls` that are not cached yet, logging failures.
+    pub async fn prefetch<I>(&self, urls: I)
+    where
+        I: IntoIterator,
+        I::Item: AsRef<str>,
+    {
+        for url in urls {
+            let url = url.as_ref();
+            if self.contains(url) {
+                continue;
+            }
+            if let Err(error) = self.get(url).await {
+                log::warn!("failed to prefetch artwork {url}: {error}");
+            }
+        }
+    }
+
+    /// Removes the image at `url` from the cache, returning whether it was cached.
+    pub fn evict(&self, url: &str) -> Result<bool> {
+        let key = cache_key(url);
+        if self.index().remove(&key).is_none() {
+            return Ok(false);
+        }
+        self.remove_file(&key)?;
+        Ok(true)
+    }
+
+    /// Removes every cached image.
+    pub fn clear(&self) -> Result<()> {
+        let keys: Vec<String> = self.index().evict_to(0);
+        for key in keys {
+            self.remove_file(&key)?;
+        }
+        Ok(())
+    }
+
+    /// Starts downloading the image at `url` into the file for `key`.
+    fn download(&self, key: &str, url: Url) -> Download {
+        let http = self.http.clone();
+        let path = self.dir.join(key);
+        let partial = self.dir.join(format!("{key}{PARTIAL_SUFFIX}"));
+        async move {
+            let bytes = fetch(&http, url).await?;
+            tokio::fs::write(&partial, &bytes).await.map_err(io_error)?;
+            tokio::fs::rename(&partial, &path).await.map_err(io_error)?;
+            Ok(Arc::new(bytes))
+        }
+        .map_err(Arc::new)
+        .boxed()
+        .shared()
+    }
+
+    fn evict_over_budget(&self) {
+        let evicted = self.index().evict_to(self.max_size);
+        for key in evicted {
+            if let Err(error) = self.remove_file(&key) {
+                log::warn!("failed to evict cached artwork {key}: {error}");
+            }
+        }
+    }
+
+    fn remove_file(&self, key: &str) -> Result<()> {
+        match std::fs::remove_file(self.dir.join(key)) {
+            Err(error) if error.kind() != ErrorKind::NotFound => Err(io_error(error)),
+            _ => Ok(()),
+        }
+    }
+
+    fn index(&self) -> std::sync::MutexGuard<'_, Index> {
+        self.index.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    fn downloads(&self) -> std::sync::MutexGuard<'_, HashMap<String, Download>> {
+        self.downloads
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+    }
+}
+
+/// Downloads an image, refusing bodies larger than [`MAX_DOWNLOAD_SIZE`].
+async fn fetch(http: &reqwest::Client, url: Url) -> Result<Vec<u8>> {
+    let mut response = http.get(url).send().await?;
+    let status = response.status();
+    if !status.is_success() {
+        return Err(Error::Api {
+            status: status.as_u16(),
+            message: format!("artwork request failed with {status}"),
+        });
+    }
+    let too_large = || Error::Http(format!("artwork is larger than {MAX_DOWNLOAD_SIZE} bytes"));
+    let length = response.content_length().unwrap_or_default();
+    if length > MAX_DOWNLOAD_SIZE {
+        return Err(too_large());
+    }
+    let mut bytes = Vec::with_capacity(length as usize);
+    while let Some(chunk) = response.chunk().await? {
+        if (bytes.len() + chunk.len()) as u64 > MAX_DOWNLOAD_SIZE {
+            return Err(too_large());
+        }
+        bytes.extend_from_slice(&chunk);
+    }
+    Ok(bytes)
+}
+
+/// The error a shared download failed with, for one of the requests awaiting it.
+///
+/// Downloads only fail with these variants; anything else keeps its message.
+fn download_error(error: &Error) -> Error {
+    match error {
+        Error::Api { status, message } => Error::Api {
+            status: *status,
+            message: message.clone(),
+        },
+        Error::Http(message) => Error::Http(message.clone()),
+        Error::Io(message) => Error::Io(message.clone()),
+        error => Error::Io(error.to_string()),
+    }
+}
+
+/// The URL a webview loads the image at `url` from through the cache.
+///
+/// In JavaScript, `convertFileSrc(url, 'musickit-artwork')` builds the same URL.
+pub fn protocol_url(url: &str) -> String {
+    let encoded = percent_encode(url);
+    if cfg!(any(windows, target_os = "android")) {
+        format!("http://{ARTWORK_SCHEME}.localhost/{encoded}")
+    } else {
+        format!("{ARTWORK_SCHEME}://localhost/{encoded}")
+    }
+}
+
+/// Parses `url` and checks that it points at Apple Music artwork.
+fn artwork_url(url: &str) -> Result<Url> {
+    let invalid = || Error::InvalidArtworkUrl(url.to_string());
+    let parsed = Url::parse(url).map_err(|_| invalid())?;
+    let host = parsed.host_str().ok_or_else(invalid)?;
+    let allowed = ARTWORK_HOSTS
+        .iter()
+        .any(|allowed| host == *allowed || host.ends_with(&format!(".{allowed}")));
+    if parsed.scheme() != "https" || !allowed {
+        return Err(invalid());
+    }
+    Ok(parsed)
+}
+
+/// A file name for `url`: its 64-bit FNV-1a hash, which stays the same across builds.
+fn cache_key(url: &str) -> String {
+    let hash = url.bytes().fold(0xcbf2_9ce4_8422_2325_u64, |hash, byte| {
+        (hash ^ u64::from(byte)).wrapping_mul(0x0100_0000_01b3)
+    });
+    format!("{hash:016x}")
+}
+
+fn io_error(error: std::io::Error) -> Error {
+    Error::Io(error.to_string())
+}
+
+fn percent_encode(value: &str) -> String {
+    let mut encoded = String::with_capacity(value.len());
+    for byte in value.bytes() {
+        if byte.is_ascii_alphanumeric() || matches!(byte, b'-' | b'_' | b'.' | b'~') {
+            encoded.push(char::from(byte));
+        } else {
+            encoded.push_str(&format!("%{byte:02X}"));
+        }
+    }
+    encoded
+}
+
+fn percent_decode(value: &str) -> Option<String> {
+    let bytes = value.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        if bytes[i] == b'%' {
+            // `from_str_radix` alone would accept a sign, as in `%+1`.
+            let hex = value.get(i + 1..i + 3)?;
+            if !hex.bytes().all(|byte| byte.is_ascii_hexdigit()) {
+                return None;
+            }
+            decoded.push(u8::from_str_radix(hex, 16).ok()?);
+            i += 3;
+        } else {
+            decoded.push(bytes[i]);
+            i += 1;
+        }
+    }
+    String::from_utf8(decoded).ok()
+}
+
+/// The content type of an image, sniffed from its first bytes.
+fn content_type(bytes: &[u8]) -> &'static str {
+    match bytes {
+        [0xff, 0xd8, 0xff, ..] => "image/jpeg",
+        [0x89, b'P', b'N', b'G', ..] => "image/png",
+        [b'R', b'I', b'F', b'F', _, _, _, _, b'W', b'E', b'B', b'P', ..] => "image/webp",
+        _ => "application/octet-stream",
+    }
+}
+
+/// Answers a `musickit-artwork` request from the plugin's [`ArtworkCache`].
+pub(crate) fn protocol<R: Runtime>(
+    ctx: UriSchemeContext<'_, R>,
+    request: Request<Vec<u8>>,
+    responder: UriSchemeResponder,
+) {
+    let cache = ctx
+        .app_handle()
+        .try_state::<MusicKit>()
+        .and_then(|musickit| musickit.artwork_cache().cloned());
+    tauri::async_runtime::spawn(async move {
+        let response = match cache {
+            Some(cache) => serve(&cache, request.uri().path()).await,
+            None => plain_response(StatusCode::NOT_FOUND, "the artwork cache is disabled"),
+        };
+        responder.respond(response);
+    });
+}
+
+async fn serve(cache: &ArtworkCache, path: &str) -> Response<Vec<u8>> {
+    let Some(url) = percent_decode(path.trim_start_matches('/')) else {
+        return plain_response(StatusCode::BAD_REQUEST, "malformed artwork URL");
+    };
+    match cache.get(&url).await {
+        Ok(bytes) => Response::builder()
+            .header(header::CONTENT_TYPE, content_type(&bytes))
+            .header(header::CACHE_CONTROL, "max-age=31536000, immutable")
+            .header(header::ACCESS_CONTROL_ALLOW_ORIGIN, "*")
+            .body(bytes)
+            .unwrap_or_default(),
+        Err(error) => {
+            let status = match error.code() {
+                ErrorCode::InvalidArgument => StatusCode::BAD_REQUEST,
+                ErrorCode::ContentUnavailable => StatusCode::NOT_FOUND,
+                _ => StatusCode::BAD_GATEWAY,
+            };
+            plain_response(status, &error.to_string())
+        }
+    }
+}
+
+fn plain_response(status: StatusCode, message: &str) -> Response<Vec<u8>> {
+    Response::builder()
+        .status(status)
+        .header(header::CONTENT_TYPE, "text/plain")
+        .body(message.as_bytes().to_vec())
+        .unwrap_or_default()
+}
+
+/// Prefetches the artwork of the `count` items after the current one whenever the
+/// queue or the current track changes.
+pub(crate) fn prefetch_upcoming<R: Runtime>(
+    app: &AppHandle<R>,
+    cache: Arc<ArtworkCache>,
+    count: usize,
+    size: u32,
+) {
+    let prefetch = move |queue: QueueUpdateEvent| {
+        let urls: Vec<String> = queue
+            .items
+            .iter()
+            .skip(queue.position + 1)
+            .take(count)
+            .filter_map(|track| track.artwork_url(size, size))
+            .collect();
+        if urls.is_empty() {
+            return;
+        }
+        let cache = cache.clone();
+        tauri::async_runtime::spawn(async move { cache.prefetch(urls).await });
+    };
+
+    let on_queue = prefetch.clone();
+    app.listen_any(
+        PLAYER_ADAPTER_EVENTS_QUEUE_UPDATE,
+        move |event| match serde_json::from_str(event.payload()) {
+            Ok(queue) => on_queue(queue),
+            Err(error) => log::warn!("failed to parse queue update: {error}"),
+        },
+    );
+
+    // Track changes move the cursor without a queue update, so read the queue.
+    let handle = app.clone();
+    app.listen_any(PLAYER_ADAPTER_EVENTS_TRACK_CHANGE, move |_| {
+        let handle = handle.clone();
+        let prefetch = prefetch.clone();
+        tauri::async_runtime::spawn_blocking(move || match handle.music_kit().get_queue() {
+            Ok(queue) => prefetch(QueueUpdateEvent {
+                items: queue.items,
+                position: queue.position,
+            }),
+            Err(error) => log::warn!("failed to read the queue for prefetching: {error}"),
+        });
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn index(entries: &[(&str, u64)]) -> Index {
+        let mut index = Index::default();
+        for (key, size) in entries {
+            index.insert(key.to_string(), *size);
+        }
+        index
+    }
+
+    #[test]
+    fn evict_to_removes_the_least_recently_used_first() {
+        let mut index = index(&[("a", 10), ("b", 20), ("c", 30)]);
+        assert!(index.touch("a"));
+
+        assert_eq!(index.evict_to(15), ["b", "c"]);
+        assert_eq!(index.size, 10);
+        assert!(index.entries.contains_key("a"));
+        assert_eq!(index.by_use.len(), 1);
+    }
+
+    #[test]
+    fn evict_to_keeps_an_index_within_budget() {
+        let mut index = index(&[("a", 10), ("b", 20)]);
+
+        assert!(index.evict_to(30).is_empty());
+        assert_eq!(index.evict_to(0), ["a", "b"]);
+        assert_eq!(index.size, 0);
+        assert!(index.entries.is_empty());
+        assert!(index.by_use.is_empty());
+    }
+
+    #[test]
+    fn reinserting_an_image_replaces_its_size_and_use() {
+        let mut index = index(&[("a", 10), ("b", 20)]);
+        index.insert("a".to_string(), 5);
+
+        assert_eq!(index.size, 25);
+        assert!(!index.touch("c"));
+        assert_eq!(index.evict_to(5), ["b"]);
+    }
+
+    #[test]
+    fn percent_decode_reverses_percent_encode() {
+        let url = "https://is1-ssl.mzstatic.com/image/thumb/a b/{w}x{h}bb.jpg?é=1";
+        assert_eq!(percent_decode(&percent_encode(url)).as_deref(), Some(url));
+        assert_eq!(percent_decode("%e2%82%AC").as_deref(), Some("€"));
+    }
+
+    #[test]
+    fn percent_decode_rejects_malformed_input() {
+        for malformed in ["%", "%4", "abc%", "%zz", "%+1", "%C3", "%FF%FE", "%é1"] {
+            assert_eq!(percent_decode(malformed), None, "{malformed}");
+        }
+    }
+}
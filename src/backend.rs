@@ -0,0 +1,105 @@
+//! The contract every MusicKit implementation fulfils.
+
+use crate::{models::*, Result};
+
+/// A MusicKit implementation the plugin can drive.
+///
+/// The plugin ships a native backend for each platform (`desktop` and `mobile`), and
+/// [`crate::init_with_backend`] accepts any other type implementing this trait. Every
+/// command in `commands.rs` goes through these methods, so all backends share one
+/// signature set.
+pub trait MusicKitBackend: Send + Sync + 'static {
+    fn initialize(&self) -> Result<()>;
+
+    fn authorize(&self) -> Result<AuthorizationResponse>;
+
+    fn unauthorize(&self) -> Result<UnauthorizeResponse>;
+
+    fn get_authorization_status(&self) -> Result<AuthorizationStatusResponse>;
+
+    fn get_user_token(&self) -> Result<GetUserTokenResponse>;
+
+    fn get_developer_token(&self) -> Result<Option<String>>;
+
+    fn set_developer_token(&self, token: String) -> Result<()>;
+
+    fn set_user_token(&self, token: String) -> Result<()>;
+
+    fn get_storefront_id(&self) -> Result<Option<String>>;
+
+    fn get_storefront(&self) -> Result<Option<serde_json::Value>>;
+
+    fn set_storefront(&self, storefront: String) -> Result<()>;
+
+    fn get_queue(&self) -> Result<QueueResponse>;
+
+    fn play(&self) -> Result<()>;
+
+    fn pause(&self) -> Result<()>;
+
+    fn stop(&self) -> Result<()>;
+
+    fn seek(&self, time: f64) -> Result<()>;
+
+    fn next(&self) -> Result<()>;
+
+    fn previous(&self) -> Result<()>;
+
+    fn skip_to_item(&self, track_id: String, start_playing: bool) -> Result<()>;
+
+    fn set_volume(&self, volume: f64) -> Result<()>;
+
+    fn set_shuffle_mode(&self, mode: ShuffleMode) -> Result<()>;
+
+    fn get_shuffle_mode(&self) -> Result<ShuffleMode>;
+
+    fn set_repeat_mode(&self, mode: RepeatMode) -> Result<()>;
+
+    fn get_repeat_mode(&self) -> Result<RepeatMode>;
+
+    fn set_queue(
+        &self,
+        tracks: Vec<MusicKitTrack>,
+        start_playing: bool,
+        start_position: usize,
+    ) -> Result<QueueResponse>;
+
+    /// Replaces the queue with a whole container and starts playing it.
+    fn set_queue_from_container(&self, container: QueueContainer) -> Result<QueueResponse>;
+
+    fn update_queue(&self, tracks: Vec<MusicKitTrack>) -> Result<QueueResponse>;
+
+    fn insert_track_at_position(
+        &self,
+        track: MusicKitTrack,
+        position: usize,
+    ) -> Result<QueueResponse>;
+
+    fn insert_tracks_at_position(
+        &self,
+        tracks: Vec<MusicKitTrack>,
+        position: usize,
+    ) -> Result<QueueResponse>;
+
+    fn remove_track_from_queue(&self, track_id: String) -> Result<QueueResponse>;
+
+    fn remove_track_at_position(&self, position: usize) -> Result<QueueResponse>;
+
+    fn remove_range(&self, start: usize, end: usize) -> Result<QueueResponse>;
+
+    fn clear_queue(&self) -> Result<QueueResponse>;
+
+    fn insert_track_next(&self, track: MusicKitTrack) -> Result<QueueResponse>;
+
+    fn insert_track_last(&self, track: MusicKitTrack) -> Result<QueueResponse>;
+
+    fn append_tracks_to_queue(&self, tracks: Vec<MusicKitTrack>) -> Result<QueueResponse>;
+
+    fn move_queue_item(&self, from: usize, to: usize) -> Result<QueueResponse>;
+
+    fn move_queue_items(&self, indices: Vec<usize>, to: usize) -> Result<QueueResponse>;
+
+    fn get_current_track(&self) -> Result<Option<MusicKitTrack>>;
+
+    fn get_playback_state(&self) -> Result<StateUpdateEvent>;
+}
@@ -0,0 +1,208 @@
+//! Interpolating the playback position between state updates.
+
+use std::time::Instant;
+
+use crate::models::StateUpdateEvent;
+
+/// Extrapolates `current_time` from the last known position and the playback rate.
+///
+/// Anchor the clock on every [`StateUpdateEvent`] with [`PlaybackClock::update`] and
+/// read [`PlaybackClock::current_time`] as often as the UI renders; it advances in
+/// real time while playing and stands still otherwise.
+#[derive(Debug, Clone, Copy)]
+pub struct PlaybackClock {
+    anchor_time: f64,
+    anchored_at: Instant,
+    rate: f64,
+    duration: f64,
+}
+
+impl Default for PlaybackClock {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl PlaybackClock {
+    /// A stopped clock at the start of an unknown-length item.
+    pub fn new() -> Self {
+        Self {
+            anchor_time: 0.0,
+            anchored_at: Instant::now(),
+            rate: 0.0,
+            duration: 0.0,
+        }
+    }
+
+    /// A clock anchored on `state` now.
+    pub fn from_state(state: &StateUpdateEvent) -> Self {
+        let mut clock = Self::new();
+        clock.update(state);
+        clock
+    }
+
+    /// Re-anchors the clock on `state`, running at normal speed while it is playing.
+    pub fn update(&mut self, state: &StateUpdateEvent) {
+        self.update_at(state, Instant::now());
+    }
+
+    /// Re-anchors the clock on `state` as observed at `now`.
+    pub fn update_at(&mut self, state: &StateUpdateEvent, now: Instant) {
+        self.anchor_time = state.current_time;
+        self.anchored_at = now;
+        self.rate = if state.playing { 1.0 } else { 0.0 };
+        self.duration = state.duration;
+    }
+
+    /// Changes the playback rate from now on, keeping the current position.
+    pub fn set_rate(&mut self, rate: f64) {
+        self.set_rate_at(rate, Instant::now());
+    }
+
+    /// Changes the playback rate from `now` on, keeping the position at `now`.
+    pub fn set_rate_at(&mut self, rate: f64, now: Instant) {
+        self.anchor_time = self.current_time_at(now);
+        self.anchored_at = now;
+        self.rate = rate;
+    }
+
+    pub fn rate(&self) -> f64 {
+        self.rate
+    }
+
+    pub fn duration(&self) -> f64 {
+        self.duration
+    }
+
+    /// Whether the position is advancing.
+    pub fn is_running(&self) -> bool {
+        self.rate != 0.0
+    }
+
+    /// The interpolated position in seconds.
+    pub fn current_time(&self) -> f64 {
+        self.current_time_at(Instant::now())
+    }
+
+    /// The interpolated position in seconds at `now`, clamped to the item's duration
+    /// when it is known.
+    pub fn current_time_at(&self, now: Instant) -> f64 {
+        let elapsed = now
+            .saturating_duration_since(self.anchored_at)
+            .as_secs_f64();
+        let time = (self.anchor_time + elapsed * self.rate).max(0.0);
+        if self.duration > 0.0 {
+            time.min(self.duration)
+        } else {
+            time
+        }
+    }
+
+    /// The interpolated position as a fraction of the duration, or `0.0` when the
+    /// duration is unknown.
+    pub fn progress(&self) -> f64 {
+        if self.duration > 0.0 {
+            self.current_time() / self.duration
+        } else {
+            0.0
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::*;
+    use crate::models::{RepeatMode, ShuffleMode};
+
+    fn state(playing: bool, current_time: f64, duration: f64) -> StateUpdateEvent {
+        StateUpdateEvent {
+            playing,
+            paused: !playing,
+            current_track: None,
+            current_time,
+            duration,
+            progress: 0.0,
+            queue_position: 0,
+            shuffle_mode: ShuffleMode::Off,
+            repeat_mode: RepeatMode::None,
+            volume: 1.0,
+        }
+    }
+
+    fn secs(secs: f64) -> Duration {
+        Duration::from_secs_f64(secs)
+    }
+
+    #[test]
+    fn advances_in_real_time_while_playing() {
+        let start = Instant::now();
+        let mut clock = PlaybackClock::new();
+        clock.update_at(&state(true, 10.0, 200.0), start);
+        assert!(clock.is_running());
+        assert_eq!(clock.current_time_at(start), 10.0);
+        assert_eq!(clock.current_time_at(start + secs(2.5)), 12.5);
+    }
+
+    #[test]
+    fn stands_still_while_paused() {
+        let start = Instant::now();
+        let mut clock = PlaybackClock::new();
+        clock.update_at(&state(false, 30.0, 200.0), start);
+        assert!(!clock.is_running());
+        assert_eq!(clock.current_time_at(start + secs(60.0)), 30.0);
+
+        // Resuming anchors on the new update.
+        clock.update_at(&state(true, 30.0, 200.0), start + secs(60.0));
+        assert_eq!(clock.current_time_at(start + secs(61.0)), 31.0);
+    }
+
+    #[test]
+    fn runs_at_the_set_rate() {
+        let start = Instant::now();
+        let mut clock = PlaybackClock::new();
+        clock.update_at(&state(true, 10.0, 200.0), start);
+        clock.set_rate_at(2.0, start + secs(4.0));
+        assert_eq!(clock.rate(), 2.0);
+        assert_eq!(clock.current_time_at(start + secs(4.0)), 14.0);
+        assert_eq!(clock.current_time_at(start + secs(7.0)), 20.0);
+
+        clock.set_rate_at(0.5, start + secs(7.0));
+        assert_eq!(clock.current_time_at(start + secs(9.0)), 21.0);
+    }
+
+    #[test]
+    fn a_zero_rate_pauses_and_a_negative_rate_rewinds_to_the_start() {
+        let start = Instant::now();
+        let mut clock = PlaybackClock::new();
+        clock.update_at(&state(true, 10.0, 200.0), start);
+        clock.set_rate_at(0.0, start + secs(1.0));
+        assert!(!clock.is_running());
+        assert_eq!(clock.current_time_at(start + secs(5.0)), 11.0);
+
+        clock.set_rate_at(-2.0, start + secs(5.0));
+        assert_eq!(clock.current_time_at(start + secs(7.0)), 7.0);
+        assert_eq!(clock.current_time_at(start + secs(60.0)), 0.0);
+    }
+
+    #[test]
+    fn stops_at_the_end_of_the_item() {
+        let start = Instant::now();
+        let mut clock = PlaybackClock::new();
+        clock.update_at(&state(true, 195.0, 200.0), start);
+        assert_eq!(clock.current_time_at(start + secs(10.0)), 200.0);
+
+        // An unknown duration does not clamp.
+        clock.update_at(&state(true, 195.0, 0.0), start);
+        assert_eq!(clock.current_time_at(start + secs(10.0)), 205.0);
+    }
+
+    #[test]
+    fn progress_is_zero_without_a_duration() {
+        let mut clock = PlaybackClock::from_state(&state(false, 50.0, 200.0));
+        assert_eq!(clock.progress(), 0.25);
+        clock.update(&state(false, 50.0, 0.0));
+        assert_eq!(clock.progress(), 0.0);
+    }
+}
@@ -1,5 +1,15 @@
+#[cfg(feature = "api")]
+use std::sync::Arc;
+
 use tauri::{command, AppHandle, Runtime};
 
+#[cfg(feature = "api")]
+use crate::api::{
+    AlbumDetails, ArtistDetails, CatalogSearchRequest, CatalogSearchResults, DetailRequest,
+    PlaylistDetails, SearchSuggestions, SearchSuggestionsRequest, SearchType, SuggestionKind,
+};
+#[cfg(feature = "api")]
+use crate::{artwork_cache::ArtworkCache, Error};
 use crate::{models::*, MusicKitExt, Result};
 
 #[command(rename_all = "camelCase")]
@@ -32,7 +42,7 @@ pub fn get_user_token<R: Runtime>(app: AppHandle<R>) -> Result<Option<String>> {
 
 #[command(rename_all = "camelCase")]
 pub fn get_developer_token<R: Runtime>(app: AppHandle<R>) -> Result<Option<String>> {
-    app.music_kit().get_developer_token()
+    app.music_kit().developer_token()
 }
 
 #[command(rename_all = "camelCase")]
@@ -55,6 +65,11 @@ pub fn get_storefront<R: Runtime>(app: AppHandle<R>) -> Result<Option<serde_json
     app.music_kit().get_storefront()
 }
 
+#[command(rename_all = "camelCase")]
+pub fn set_storefront<R: Runtime>(app: AppHandle<R>, storefront: String) -> Result<()> {
+    app.music_kit().set_storefront(storefront)
+}
+
 #[command(rename_all = "camelCase")]
 pub fn get_queue<R: Runtime>(app: AppHandle<R>) -> Result<QueueResponse> {
     app.music_kit().get_queue()
@@ -104,13 +119,33 @@ pub fn set_volume<R: Runtime>(app: AppHandle<R>, volume: f64) -> Result<()> {
     app.music_kit().set_volume(volume)
 }
 
+#[command(rename_all = "camelCase")]
+pub fn set_shuffle_mode<R: Runtime>(app: AppHandle<R>, mode: ShuffleMode) -> Result<()> {
+    app.music_kit().set_shuffle_mode(mode)
+}
+
+#[command(rename_all = "camelCase")]
+pub fn get_shuffle_mode<R: Runtime>(app: AppHandle<R>) -> Result<ShuffleMode> {
+    app.music_kit().get_shuffle_mode()
+}
+
+#[command(rename_all = "camelCase")]
+pub fn set_repeat_mode<R: Runtime>(app: AppHandle<R>, mode: RepeatMode) -> Result<()> {
+    app.music_kit().set_repeat_mode(mode)
+}
+
+#[command(rename_all = "camelCase")]
+pub fn get_repeat_mode<R: Runtime>(app: AppHandle<R>) -> Result<RepeatMode> {
+    app.music_kit().get_repeat_mode()
+}
+
 #[command(rename_all = "camelCase")]
 pub fn set_queue<R: Runtime>(
     app: AppHandle<R>,
     tracks: Vec<MusicKitTrack>,
     start_playing: bool,
     start_position: usize,
-) -> Result<QueueOperationResponse> {
+) -> Result<QueueResponse> {
     app.music_kit()
         .set_queue(tracks, start_playing, start_position)
 }
@@ -119,7 +154,7 @@ pub fn set_queue<R: Runtime>(
 pub fn update_queue<R: Runtime>(
     app: AppHandle<R>,
     tracks: Vec<MusicKitTrack>,
-) -> Result<QueueOperationResponse> {
+) -> Result<QueueResponse> {
     app.music_kit().update_queue(tracks)
 }
 
@@ -128,7 +163,7 @@ pub fn insert_track_at_position<R: Runtime>(
     app: AppHandle<R>,
     track: MusicKitTrack,
     position: usize,
-) -> Result<QueueOperationResponse> {
+) -> Result<QueueResponse> {
     app.music_kit().insert_track_at_position(track, position)
 }
 
@@ -137,7 +172,7 @@ pub fn insert_tracks_at_position<R: Runtime>(
     app: AppHandle<R>,
     tracks: Vec<MusicKitTrack>,
     position: usize,
-) -> Result<QueueOperationResponse> {
+) -> Result<QueueResponse> {
     app.music_kit().insert_tracks_at_position(tracks, position)
 }
 
@@ -145,7 +180,7 @@ pub fn insert_tracks_at_position<R: Runtime>(
 pub fn remove_track_from_queue<R: Runtime>(
     app: AppHandle<R>,
     track_id: String,
-) -> Result<QueueOperationResponse> {
+) -> Result<QueueResponse> {
     app.music_kit().remove_track_from_queue(track_id)
 }
 
@@ -153,7 +188,7 @@ pub fn remove_track_from_queue<R: Runtime>(
 pub fn insert_track_next<R: Runtime>(
     app: AppHandle<R>,
     track: MusicKitTrack,
-) -> Result<QueueOperationResponse> {
+) -> Result<QueueResponse> {
     app.music_kit().insert_track_next(track)
 }
 
@@ -161,7 +196,7 @@ pub fn insert_track_next<R: Runtime>(
 pub fn insert_track_last<R: Runtime>(
     app: AppHandle<R>,
     track: MusicKitTrack,
-) -> Result<QueueOperationResponse> {
+) -> Result<QueueResponse> {
     app.music_kit().insert_track_last(track)
 }
 
@@ -169,10 +204,60 @@ pub fn insert_track_last<R: Runtime>(
 pub fn append_tracks_to_queue<R: Runtime>(
     app: AppHandle<R>,
     tracks: Vec<MusicKitTrack>,
-) -> Result<QueueOperationResponse> {
+) -> Result<QueueResponse> {
     app.music_kit().append_tracks_to_queue(tracks)
 }
 
+#[command(rename_all = "camelCase")]
+pub fn remove_track_at_position<R: Runtime>(
+    app: AppHandle<R>,
+    position: usize,
+) -> Result<QueueResponse> {
+    app.music_kit().remove_track_at_position(position)
+}
+
+#[command(rename_all = "camelCase")]
+pub fn remove_range<R: Runtime>(
+    app: AppHandle<R>,
+    start: usize,
+    end: usize,
+) -> Result<QueueResponse> {
+    app.music_kit().remove_range(start, end)
+}
+
+#[command(rename_all = "camelCase")]
+pub fn clear_queue<R: Runtime>(app: AppHandle<R>) -> Result<QueueResponse> {
+    app.music_kit().clear_queue()
+}
+
+#[command(rename_all = "camelCase")]
+pub fn move_queue_item<R: Runtime>(
+    app: AppHandle<R>,
+    from: usize,
+    to: usize,
+) -> Result<QueueResponse> {
+    app.music_kit().move_queue_item(from, to)
+}
+
+#[command(rename_all = "camelCase")]
+pub fn move_queue_items<R: Runtime>(
+    app: AppHandle<R>,
+    indices: Vec<usize>,
+    to: usize,
+) -> Result<QueueResponse> {
+    app.music_kit().move_queue_items(indices, to)
+}
+
+#[command(rename_all = "camelCase")]
+pub fn undo_queue_change<R: Runtime>(app: AppHandle<R>) -> Result<QueueResponse> {
+    app.music_kit().undo_queue_change()
+}
+
+#[command(rename_all = "camelCase")]
+pub fn redo_queue_change<R: Runtime>(app: AppHandle<R>) -> Result<QueueResponse> {
+    app.music_kit().redo_queue_change()
+}
+
 #[command(rename_all = "camelCase")]
 pub fn get_current_track<R: Runtime>(app: AppHandle<R>) -> Result<Option<MusicKitTrack>> {
     app.music_kit().get_current_track()
@@ -182,3 +267,155 @@ pub fn get_current_track<R: Runtime>(app: AppHandle<R>) -> Result<Option<MusicKi
 pub fn get_playback_state<R: Runtime>(app: AppHandle<R>) -> Result<StateUpdateEvent> {
     app.music_kit().get_playback_state()
 }
+
+#[cfg(feature = "api")]
+#[command(rename_all = "camelCase")]
+pub async fn search_catalog<R: Runtime>(
+    app: AppHandle<R>,
+    term: String,
+    types: Vec<SearchType>,
+    limit: Option<u32>,
+    offset: Option<u32>,
+    storefront: Option<String>,
+) -> Result<CatalogSearchResults> {
+    let client = app.music_kit().api_client()?;
+    client
+        .search_catalog(&CatalogSearchRequest {
+            term,
+            types,
+            limit,
+            offset,
+            storefront,
+        })
+        .await
+}
+
+#[cfg(feature = "api")]
+#[command(rename_all = "camelCase")]
+pub async fn get_search_suggestions<R: Runtime>(
+    app: AppHandle<R>,
+    term: String,
+    kinds: Vec<SuggestionKind>,
+    types: Option<Vec<SearchType>>,
+    limit: Option<u32>,
+) -> Result<Option<SearchSuggestions>> {
+    app.music_kit()
+        .get_search_suggestions(SearchSuggestionsRequest {
+            term,
+            kinds,
+            types: types.unwrap_or_default(),
+            limit,
+            storefront: None,
+        })
+        .await
+}
+
+#[cfg(feature = "api")]
+fn artwork_cache<R: Runtime>(app: &AppHandle<R>) -> Result<Arc<ArtworkCache>> {
+    app.music_kit()
+        .artwork_cache()
+        .cloned()
+        .ok_or_else(|| Error::InvalidConfig("`artworkCache` is not configured".to_string()))
+}
+
+#[cfg(feature = "api")]
+#[command(rename_all = "camelCase")]
+pub async fn prefetch_artwork<R: Runtime>(app: AppHandle<R>, urls: Vec<String>) -> Result<()> {
+    artwork_cache(&app)?.prefetch(urls).await;
+    Ok(())
+}
+
+#[cfg(feature = "api")]
+#[command(rename_all = "camelCase")]
+pub fn evict_artwork<R: Runtime>(app: AppHandle<R>, url: String) -> Result<bool> {
+    artwork_cache(&app)?.evict(&url)
+}
+
+#[cfg(feature = "api")]
+#[command(rename_all = "camelCase")]
+pub fn clear_artwork_cache<R: Runtime>(app: AppHandle<R>) -> Result<()> {
+    artwork_cache(&app)?.clear()
+}
+
+#[cfg(feature = "api")]
+#[command(rename_all = "camelCase")]
+pub async fn get_album<R: Runtime>(
+    app: AppHandle<R>,
+    id: String,
+    include: Option<Vec<String>>,
+    extend: Option<Vec<String>>,
+    storefront: Option<String>,
+    library: Option<bool>,
+    max_items: Option<usize>,
+) -> Result<AlbumDetails> {
+    let request = detail_request(id, include, extend, storefront, library, max_items);
+    app.music_kit().api_client()?.get_album(&request).await
+}
+
+#[cfg(feature = "api")]
+#[command(rename_all = "camelCase")]
+pub async fn get_artist<R: Runtime>(
+    app: AppHandle<R>,
+    id: String,
+    include: Option<Vec<String>>,
+    extend: Option<Vec<String>>,
+    storefront: Option<String>,
+    library: Option<bool>,
+    max_items: Option<usize>,
+) -> Result<ArtistDetails> {
+    let request = detail_request(id, include, extend, storefront, library, max_items);
+    app.music_kit().api_client()?.get_artist(&request).await
+}
+
+#[cfg(feature = "api")]
+#[command(rename_all = "camelCase")]
+pub async fn get_playlist<R: Runtime>(
+    app: AppHandle<R>,
+    id: String,
+    include: Option<Vec<String>>,
+    extend: Option<Vec<String>>,
+    storefront: Option<String>,
+    library: Option<bool>,
+    max_items: Option<usize>,
+) -> Result<PlaylistDetails> {
+    let request = detail_request(id, include, extend, storefront, library, max_items);
+    app.music_kit().api_client()?.get_playlist(&request).await
+}
+
+#[cfg(feature = "api")]
+fn detail_request(
+    id: String,
+    include: Option<Vec<String>>,
+    extend: Option<Vec<String>>,
+    storefront: Option<String>,
+    library: Option<bool>,
+    max_items: Option<usize>,
+) -> DetailRequest {
+    DetailRequest {
+        id,
+        include: include.unwrap_or_default(),
+        extend: extend.unwrap_or_default(),
+        storefront,
+        library: library.unwrap_or_default(),
+        max_items,
+    }
+}
+
+#[cfg(feature = "api")]
+#[command(rename_all = "camelCase")]
+pub async fn set_queue_from_container<R: Runtime>(
+    app: AppHandle<R>,
+    kind: ContainerKind,
+    id: String,
+    start_index: Option<usize>,
+    shuffle: Option<bool>,
+) -> Result<QueueResponse> {
+    app.music_kit()
+        .set_queue_from_container(
+            kind,
+            id,
+            start_index.unwrap_or_default(),
+            shuffle.unwrap_or_default(),
+        )
+        .await
+}
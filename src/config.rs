@@ -0,0 +1,280 @@
+//! The `plugins.musickit` section of `tauri.conf.json`.
+
+use std::path::PathBuf;
+
+use serde::{Deserialize, Serialize};
+
+use crate::{Error, Result};
+
+/// Plugin configuration, read from `plugins.musickit` in `tauri.conf.json`.
+///
+/// ```json
+/// {
+///   "plugins": {
+///     "musickit": {
+///       "developerKey": { "teamId": "ABCDE12345", "keyId": "KEY1234567", "keyPath": "AuthKey.p8" },
+///       "storefront": "us",
+///       "autoInitialize": true,
+///       "eventThrottleMs": 250,
+///       "persistQueue": true,
+///       "restoreQueue": true,
+///       "artworkCache": { "maxSizeMb": 200, "prefetchCount": 5 }
+///     }
+///   }
+/// }
+/// ```
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+pub struct Config {
+    /// A pre-signed developer token. Mutually exclusive with `developer_key`.
+    pub developer_token: Option<String>,
+    /// A private key to mint developer tokens from. Requires the `jwt` feature.
+    pub developer_key: Option<DeveloperKeyConfig>,
+    /// The storefront used for catalog requests until the user's own is known.
+    pub storefront: Option<String>,
+    /// Calls `initialize` during plugin setup.
+    #[serde(default)]
+    pub auto_initialize: bool,
+    /// Minimum interval between state-update events, in milliseconds.
+    pub event_throttle_ms: Option<u64>,
+    /// Saves the queue and playback time to the app data directory as they change.
+    #[serde(default)]
+    pub persist_queue: bool,
+    /// Restores the saved queue, paused, the first time `initialize` is called.
+    #[serde(default)]
+    pub restore_queue: bool,
+    /// How long queue changes are coalesced before being saved, in milliseconds.
+    pub persist_debounce_ms: Option<u64>,
+    /// Serves artwork through the `musickit-artwork` URI scheme from a disk cache.
+    /// Requires the `api` feature.
+    pub artwork_cache: Option<ArtworkCacheConfig>,
+    /// The backend to drive.
+    #[serde(default)]
+    pub backend: BackendKind,
+}
+
+/// The MusicKit private key developer tokens are minted from.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+pub struct DeveloperKeyConfig {
+    pub team_id: String,
+    pub key_id: String,
+    /// Path to the `.p8` key file.
+    pub key_path: PathBuf,
+    /// Lifetime of minted tokens in seconds.
+    pub expiry_secs: Option<u64>,
+    /// Web origins to restrict minted tokens to.
+    #[serde(default)]
+    pub origins: Vec<String>,
+}
+
+/// The disk cache behind the `musickit-artwork` URI scheme.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+pub struct ArtworkCacheConfig {
+    /// The most disk space cached artwork may use, in megabytes.
+    pub max_size_mb: Option<u64>,
+    /// How many items after the current one have their artwork prefetched. `0`
+    /// disables prefetching.
+    pub prefetch_count: Option<usize>,
+    /// The edge, in pixels, prefetched artwork is rendered at.
+    pub prefetch_size: Option<u32>,
+}
+
+/// Which [`crate::MusicKitBackend`] the plugin drives.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum BackendKind {
+    /// The native implementation for the current platform.
+    #[default]
+    Native,
+    /// The in-memory mock. Requires the `mock` feature.
+    Mock,
+}
+
+impl Config {
+    /// Checks the configuration for conflicting or unusable options.
+    pub fn validate(&self) -> Result<()> {
+        if self.developer_token.is_some() && self.developer_key.is_some() {
+            return Err(Error::InvalidConfig(
+                "`developerToken` and `developerKey` are mutually exclusive".to_string(),
+            ));
+        }
+        if self
+            .developer_token
+            .as_ref()
+            .is_some_and(|token| token.trim().is_empty())
+        {
+            return Err(Error::InvalidConfig(
+                "`developerToken` must not be empty".to_string(),
+            ));
+        }
+        if let Some(key) = &self.developer_key {
+            if !cfg!(feature = "jwt") {
+                return Err(Error::FeatureNotEnabled {
+                    option: "developerKey",
+                    feature: "jwt",
+                });
+            }
+            if key.team_id.trim().is_empty() || key.key_id.trim().is_empty() {
+                return Err(Error::InvalidConfig(
+                    "`developerKey.teamId` and `developerKey.keyId` must not be empty".to_string(),
+                ));
+            }
+            if key.key_path.as_os_str().is_empty() {
+                return Err(Error::InvalidConfig(
+                    "`developerKey.keyPath` must not be empty".to_string(),
+                ));
+            }
+        }
+        if let Some(storefront) = &self.storefront {
+            let valid = storefront.len() == 2 && storefront.chars().all(|c| c.is_ascii_lowercase());
+            if !valid {
+                return Err(Error::InvalidConfig(format!(
+                    "`storefront` must be a two-letter lowercase country code, got {storefront:?}"
+                )));
+            }
+        }
+        if self.artwork_cache.is_some() && !cfg!(feature = "api") {
+            return Err(Error::FeatureNotEnabled {
+                option: "artworkCache",
+                feature: "api",
+            });
+        }
+        if self.backend == BackendKind::Mock && !cfg!(feature = "mock") {
+            return Err(Error::FeatureNotEnabled {
+                option: "backend: \"mock\"",
+                feature: "mock",
+            });
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    fn config(value: serde_json::Value) -> serde_json::Result<Config> {
+        serde_json::from_value(value)
+    }
+
+    fn invalid(config: &Config) -> String {
+        match config.validate() {
+            Err(Error::InvalidConfig(message)) => message,
+            other => panic!("expected an invalid config error, got {other:?}"),
+        }
+    }
+
+    #[test]
+    fn the_documented_example_is_valid() {
+        let config = config(json!({
+            "developerKey": {
+                "teamId": "ABCDE12345",
+                "keyId": "KEY1234567",
+                "keyPath": "AuthKey.p8"
+            },
+            "storefront": "us",
+            "autoInitialize": true,
+            "eventThrottleMs": 250,
+            "persistQueue": true,
+            "restoreQueue": true,
+            "artworkCache": { "maxSizeMb": 200, "prefetchCount": 5 }
+        }))
+        .unwrap();
+        if cfg!(all(feature = "jwt", feature = "api")) {
+            config.validate().unwrap();
+        }
+        assert_eq!(config.event_throttle_ms, Some(250));
+        assert_eq!(config.backend, BackendKind::Native);
+    }
+
+    #[test]
+    fn storefronts_must_be_two_lowercase_letters() {
+        for storefront in ["US", "usa", "u", "", "u1"] {
+            let config = config(json!({ "storefront": storefront })).unwrap();
+            assert!(invalid(&config).contains("`storefront`"), "{storefront:?}");
+        }
+        config(json!({ "storefront": "gb" }))
+            .unwrap()
+            .validate()
+            .unwrap();
+    }
+
+    #[test]
+    fn developer_tokens_and_keys_must_be_usable() {
+        let empty_token = config(json!({ "developerToken": "  " })).unwrap();
+        assert!(invalid(&empty_token).contains("`developerToken`"));
+
+        let both = config(json!({
+            "developerToken": "token",
+            "developerKey": { "teamId": "TEAM", "keyId": "KEY", "keyPath": "AuthKey.p8" }
+        }))
+        .unwrap();
+        assert!(invalid(&both).contains("mutually exclusive"));
+
+        let missing_key_path = config(json!({
+            "developerKey": { "teamId": "TEAM", "keyId": "KEY" }
+        }));
+        assert!(missing_key_path
+            .unwrap_err()
+            .to_string()
+            .contains("keyPath"));
+
+        if cfg!(feature = "jwt") {
+            let empty_key_path = config(json!({
+                "developerKey": { "teamId": "TEAM", "keyId": "KEY", "keyPath": "" }
+            }))
+            .unwrap();
+            assert!(invalid(&empty_key_path).contains("`developerKey.keyPath`"));
+
+            let empty_team = config(json!({
+                "developerKey": { "teamId": "", "keyId": "KEY", "keyPath": "AuthKey.p8" }
+            }))
+            .unwrap();
+            assert!(invalid(&empty_team).contains("`developerKey.teamId`"));
+        }
+    }
+
+    #[test]
+    fn event_throttles_must_be_whole_milliseconds() {
+        for throttle in [json!(-1), json!(2.5), json!("250")] {
+            let error = config(json!({ "eventThrottleMs": throttle })).unwrap_err();
+            assert!(error.is_data(), "{throttle}");
+        }
+        let disabled = config(json!({ "eventThrottleMs": 0 })).unwrap();
+        disabled.validate().unwrap();
+    }
+
+    #[test]
+    fn unknown_keys_are_rejected() {
+        for value in [
+            json!({ "storeFront": "us" }),
+            json!({ "developerKey": {
+                "teamId": "TEAM", "keyId": "KEY", "keyPath": "AuthKey.p8", "expiry": 60
+            } }),
+            json!({ "artworkCache": { "maxSize": 200 } }),
+            json!({ "backend": "web" }),
+        ] {
+            let error = config(value.clone()).unwrap_err();
+            assert!(error.to_string().starts_with("unknown"), "{value}: {error}");
+        }
+    }
+
+    #[test]
+    fn options_needing_disabled_features_are_rejected() {
+        let mock = config(json!({ "backend": "mock" })).unwrap();
+        match (mock.validate(), cfg!(feature = "mock")) {
+            (Ok(()), true) => {}
+            (
+                Err(Error::FeatureNotEnabled {
+                    feature: "mock", ..
+                }),
+                false,
+            ) => {}
+            (result, _) => panic!("unexpected result {result:?}"),
+        }
+    }
+}
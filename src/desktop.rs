@@ -1,6 +1,14 @@
 //! The desktop-specific implementation for the plugin.
+//!
+//! MusicKit has no desktop player, so this backend never authorizes and reports an
+//! idle player. Token, storefront and playback changes are accepted without effect.
+//! The queue is kept in a [`Queue`] so it can still be built, edited and persisted,
+//! and every change is reported with `QUEUE_UPDATE`. Shuffle and repeat modes are
+//! kept as well, and setting one reports the new state with `STATE_UPDATE`.
 
-use crate::{models::*, Result};
+use std::sync::{Mutex, MutexGuard, PoisonError};
+
+use crate::{emitter::PlayerEmitter, models::*, queue::Queue, Error, MusicKitBackend, Result};
 use serde::de::DeserializeOwned;
 use tauri::{plugin::PluginApi, AppHandle, Runtime};
 
@@ -8,217 +16,261 @@ pub fn init<R: Runtime, C: DeserializeOwned>(
     app: &AppHandle<R>,
     _api: PluginApi<R, C>,
 ) -> crate::Result<MusicKitPlugin<R>> {
-    Ok(MusicKitPlugin(app.clone()))
+    Ok(MusicKitPlugin {
+        events: PlayerEmitter::new(app.clone()),
+        state: Default::default(),
+    })
 }
 
 /// Access to the MusicKit APIs.
 #[derive(Debug)]
-pub struct MusicKitPlugin<R: Runtime>(AppHandle<R>);
+pub struct MusicKitPlugin<R: Runtime> {
+    events: PlayerEmitter<R>,
+    state: Mutex<DesktopState>,
+}
+
+/// The player state the desktop backend keeps.
+#[derive(Debug, Default)]
+struct DesktopState {
+    queue: Queue,
+    shuffle_mode: ShuffleMode,
+    repeat_mode: RepeatMode,
+}
+
+impl<R: Runtime> MusicKitPlugin<R> {
+    fn state(&self) -> MutexGuard<'_, DesktopState> {
+        self.state.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    /// Emits `STATE_UPDATE` with the current state.
+    fn emit_state(&self) -> Result<()> {
+        let state = self.get_playback_state()?;
+        self.events.state_update(state);
+        Ok(())
+    }
+
+    /// Applies a queue operation and emits `QUEUE_UPDATE` when it succeeds.
+    fn edit_queue<T>(
+        &self,
+        operation: impl FnOnce(&mut Queue) -> Result<T>,
+    ) -> Result<QueueResponse> {
+        let (response, event) = {
+            let mut state = self.state();
+            operation(&mut state.queue)?;
+            (state.queue.to_response(), state.queue.to_event())
+        };
+        self.events.queue_update(event);
+        Ok(response)
+    }
+}
 
-impl<R: Runtime> crate::MusicKitPlugin<R> {
-    pub fn initialize(&self) -> Result<()> {
-        // Desktop implementation
+impl<R: Runtime> MusicKitBackend for MusicKitPlugin<R> {
+    fn initialize(&self) -> Result<()> {
         Ok(())
     }
 
-    pub fn authorize(&self) -> Result<AuthorizationResponse> {
-        // Desktop implementation - return not authorized
+    fn authorize(&self) -> Result<AuthorizationResponse> {
         Ok(AuthorizationResponse {
-            status: "notAuthorized".to_string(),
+            status: AuthorizationStatus::NotAuthorized,
             error: Some("Not supported on desktop".to_string()),
         })
     }
 
-    pub fn unauthorize(&self) -> Result<UnauthorizeResponse> {
-        // Desktop implementation
+    fn unauthorize(&self) -> Result<UnauthorizeResponse> {
         Ok(UnauthorizeResponse {
             status: "unauthorized".to_string(),
             error: None,
         })
     }
 
-    pub fn get_authorization_status(&self) -> Result<AuthorizationStatusResponse> {
-        // Desktop implementation
+    fn get_authorization_status(&self) -> Result<AuthorizationStatusResponse> {
         Ok(AuthorizationStatusResponse {
-            status: "notAuthorized".to_string(),
+            status: AuthorizationStatus::NotAuthorized,
         })
     }
 
-    pub fn get_user_token(&self) -> Result<GetUserTokenResponse> {
-        // Desktop implementation
+    fn get_user_token(&self) -> Result<GetUserTokenResponse> {
         Ok(GetUserTokenResponse { token: None })
     }
 
-    pub fn get_developer_token(&self) -> Result<Option<String>> {
-        // Desktop implementation
+    fn get_developer_token(&self) -> Result<Option<String>> {
         Ok(None)
     }
 
-    pub fn set_developer_token(&self, _token: String) -> Result<()> {
-        // Desktop implementation - not supported
+    fn set_developer_token(&self, _token: String) -> Result<()> {
         Ok(())
     }
 
-    pub fn set_user_token(&self, _token: String) -> Result<()> {
-        // Desktop implementation - not supported
+    fn set_user_token(&self, _token: String) -> Result<()> {
         Ok(())
     }
 
-    pub fn get_storefront_id(&self) -> Result<Option<String>> {
-        // Desktop implementation
+    fn get_storefront_id(&self) -> Result<Option<String>> {
         Ok(None)
     }
 
-    pub fn get_storefront(&self) -> Result<Option<serde_json::Value>> {
-        // Desktop implementation
+    fn get_storefront(&self) -> Result<Option<serde_json::Value>> {
         Ok(None)
     }
 
-    pub fn get_queue(&self) -> Result<QueueResponse> {
-        // Desktop implementation
-        Ok(QueueResponse {
-            items: vec![],
-            position: 0,
-        })
+    fn set_storefront(&self, _storefront: String) -> Result<()> {
+        Ok(())
+    }
+
+    fn get_queue(&self) -> Result<QueueResponse> {
+        Ok(self.state().queue.to_response())
     }
 
-    pub fn play(&self) -> Result<()> {
-        // Desktop implementation
+    fn play(&self) -> Result<()> {
         Ok(())
     }
 
-    pub fn pause(&self) -> Result<()> {
-        // Desktop implementation
+    fn pause(&self) -> Result<()> {
         Ok(())
     }
 
-    pub fn stop(&self) -> Result<()> {
-        // Desktop implementation
+    fn stop(&self) -> Result<()> {
         Ok(())
     }
 
-    pub fn seek(&self, _time: f64) -> Result<()> {
-        // Desktop implementation
+    fn seek(&self, _time: f64) -> Result<()> {
         Ok(())
     }
 
-    pub fn next(&self) -> Result<()> {
-        // Desktop implementation
+    fn next(&self) -> Result<()> {
         Ok(())
     }
 
-    pub fn previous(&self) -> Result<()> {
-        // Desktop implementation
+    fn previous(&self) -> Result<()> {
         Ok(())
     }
 
-    pub fn skip_to_item(&self, _track_id: String, _start_playing: bool) -> Result<()> {
-        // Desktop implementation
+    fn skip_to_item(&self, _track_id: String, _start_playing: bool) -> Result<()> {
         Ok(())
     }
 
-    pub fn set_volume(&self, _volume: f64) -> Result<()> {
-        // Desktop implementation
+    fn set_volume(&self, _volume: f64) -> Result<()> {
         Ok(())
     }
 
-    pub fn set_queue(
+    fn set_shuffle_mode(&self, mode: ShuffleMode) -> Result<()> {
+        self.state().shuffle_mode = mode;
+        self.emit_state()
+    }
+
+    fn get_shuffle_mode(&self) -> Result<ShuffleMode> {
+        Ok(self.state().shuffle_mode.clone())
+    }
+
+    fn set_repeat_mode(&self, mode: RepeatMode) -> Result<()> {
+        self.state().repeat_mode = mode;
+        self.emit_state()
+    }
+
+    fn get_repeat_mode(&self) -> Result<RepeatMode> {
+        Ok(self.state().repeat_mode.clone())
+    }
+
+    fn set_queue(
         &self,
-        _tracks: Vec<MusicKitTrack>,
+        tracks: Vec<MusicKitTrack>,
         _start_playing: bool,
-        _start_position: usize,
-    ) -> Result<QueueOperationResponse> {
-        // Desktop implementation - not supported
-        Ok(QueueOperationResponse {
-            success: false,
-            error: Some("Not supported on desktop".to_string()),
-        })
+        start_position: usize,
+    ) -> Result<QueueResponse> {
+        self.edit_queue(|queue| queue.set(tracks, start_position))
     }
 
-    pub fn update_queue(&self, _tracks: Vec<MusicKitTrack>) -> Result<QueueOperationResponse> {
-        // Desktop implementation
-        Ok(QueueOperationResponse {
-            success: false,
-            error: Some("Not supported on desktop".to_string()),
+    fn set_queue_from_container(&self, container: QueueContainer) -> Result<QueueResponse> {
+        if container.kind == ContainerKind::Station {
+            return Err(Error::PlatformNotSupported);
+        }
+        self.edit_queue(|queue| queue.set(container.tracks, container.start_index))
+    }
+
+    fn update_queue(&self, tracks: Vec<MusicKitTrack>) -> Result<QueueResponse> {
+        self.edit_queue(|queue| {
+            queue.update(tracks);
+            Ok(())
         })
     }
 
-    pub fn insert_track_at_position(
+    fn insert_track_at_position(
         &self,
-        _track: MusicKitTrack,
-        _position: usize,
-    ) -> Result<QueueOperationResponse> {
-        // Desktop implementation
-        Ok(QueueOperationResponse {
-            success: false,
-            error: Some("Not supported on desktop".to_string()),
-        })
+        track: MusicKitTrack,
+        position: usize,
+    ) -> Result<QueueResponse> {
+        self.insert_tracks_at_position(vec![track], position)
     }
 
-    pub fn insert_tracks_at_position(
+    fn insert_tracks_at_position(
         &self,
-        _tracks: Vec<MusicKitTrack>,
-        _position: usize,
-    ) -> Result<QueueOperationResponse> {
-        // Desktop implementation
-        Ok(QueueOperationResponse {
-            success: false,
-            error: Some("Not supported on desktop".to_string()),
-        })
+        tracks: Vec<MusicKitTrack>,
+        position: usize,
+    ) -> Result<QueueResponse> {
+        self.edit_queue(|queue| queue.insert(position, tracks))
     }
 
-    pub fn remove_track_from_queue(&self, _track_id: String) -> Result<QueueOperationResponse> {
-        // Desktop implementation
-        Ok(QueueOperationResponse {
-            success: false,
-            error: Some("Not supported on desktop".to_string()),
-        })
+    fn remove_track_from_queue(&self, track_id: String) -> Result<QueueResponse> {
+        self.edit_queue(|queue| queue.remove_track(&track_id))
     }
 
-    pub fn insert_track_next(&self, _track: MusicKitTrack) -> Result<QueueOperationResponse> {
-        // Desktop implementation
-        Ok(QueueOperationResponse {
-            success: false,
-            error: Some("Not supported on desktop".to_string()),
+    fn remove_track_at_position(&self, position: usize) -> Result<QueueResponse> {
+        self.edit_queue(|queue| queue.remove_at(position))
+    }
+
+    fn remove_range(&self, start: usize, end: usize) -> Result<QueueResponse> {
+        self.edit_queue(|queue| queue.remove_range(start, end))
+    }
+
+    fn clear_queue(&self) -> Result<QueueResponse> {
+        self.edit_queue(|queue| {
+            queue.clear();
+            Ok(())
         })
     }
 
-    pub fn insert_track_last(&self, _track: MusicKitTrack) -> Result<QueueOperationResponse> {
-        // Desktop implementation
-        Ok(QueueOperationResponse {
-            success: false,
-            error: Some("Not supported on desktop".to_string()),
+    fn insert_track_next(&self, track: MusicKitTrack) -> Result<QueueResponse> {
+        self.edit_queue(|queue| {
+            queue.insert_next(vec![track]);
+            Ok(())
         })
     }
 
-    pub fn append_tracks_to_queue(
-        &self,
-        _tracks: Vec<MusicKitTrack>,
-    ) -> Result<QueueOperationResponse> {
-        // Desktop implementation
-        Ok(QueueOperationResponse {
-            success: false,
-            error: Some("Not supported on desktop".to_string()),
+    fn insert_track_last(&self, track: MusicKitTrack) -> Result<QueueResponse> {
+        self.append_tracks_to_queue(vec![track])
+    }
+
+    fn append_tracks_to_queue(&self, tracks: Vec<MusicKitTrack>) -> Result<QueueResponse> {
+        self.edit_queue(|queue| {
+            queue.append(tracks);
+            Ok(())
         })
     }
 
-    pub fn get_current_track(&self) -> Result<Option<MusicKitTrack>> {
-        // Desktop implementation
-        Ok(None)
+    fn move_queue_item(&self, from: usize, to: usize) -> Result<QueueResponse> {
+        self.move_queue_items(vec![from], to)
+    }
+
+    fn move_queue_items(&self, indices: Vec<usize>, to: usize) -> Result<QueueResponse> {
+        self.edit_queue(|queue| queue.move_items(&indices, to))
+    }
+
+    fn get_current_track(&self) -> Result<Option<MusicKitTrack>> {
+        Ok(self.state().queue.current().cloned())
     }
 
-    pub fn get_playback_state(&self) -> Result<StateUpdateEvent> {
-        // Desktop implementation
+    fn get_playback_state(&self) -> Result<StateUpdateEvent> {
+        let state = self.state();
         Ok(StateUpdateEvent {
             playing: false,
             paused: false,
-            current_track: None,
+            current_track: state.queue.current().cloned(),
             current_time: 0.0,
             duration: 0.0,
             progress: 0.0,
-            queue_position: 0,
-            shuffle_mode: "off".to_string(),
-            repeat_mode: "none".to_string(),
+            queue_position: state.queue.position(),
+            shuffle_mode: state.shuffle_mode.clone(),
+            repeat_mode: state.repeat_mode.clone(),
             volume: 1.0,
         })
     }
@@ -0,0 +1,279 @@
+//! Typed emission of the `PLAYER_ADAPTER_EVENTS_*` events.
+//!
+//! Every backend reports player changes through [`PlayerEmitter`], so each event
+//! name always carries the same payload type. Events are emitted on the app with
+//! [`tauri::Emitter::emit`], which delivers them to webview listeners and to Rust
+//! listeners registered with `app.listen` alike.
+//!
+//! When `eventThrottleMs` is configured, state updates are coalesced by the managed
+//! [`StateThrottle`] before they are emitted.
+//!
+//! The native plugins fire their own [`native`] triggers, which
+//! [`PlayerEmitter::forward_native`] normalizes into the same events.
+
+use std::{sync::Arc, time::Instant};
+
+use serde::Serialize;
+use tauri::{AppHandle, Emitter, Manager, Runtime};
+
+use crate::{
+    events::*,
+    models::*,
+    queue::Queue,
+    throttle::{StateThrottle, Throttled},
+    MusicKitBackend, Result,
+};
+
+/// The triggers the native plugins fire. Their payloads are only a signal.
+#[cfg_attr(not(any(mobile, feature = "mock")), allow(dead_code))]
+pub(crate) mod native {
+    /// Fired with the full playback state.
+    pub(crate) const PLAYBACK_STATE_CHANGED: &str = "musickit-playback-state-changed";
+    /// Fired with the full playback state of the new item.
+    pub(crate) const TRACK_CHANGED: &str = "musickit-track-changed";
+    /// Fired every second while playing.
+    pub(crate) const PLAYBACK_TIME_CHANGED: &str = "musickit-playback-time-changed";
+    /// Fired after the queue was replaced, rearranged or cleared.
+    pub(crate) const QUEUE_CHANGED: &str = "musickit-queue-changed";
+
+    pub(crate) const ALL: [&str; 4] = [
+        PLAYBACK_STATE_CHANGED,
+        TRACK_CHANGED,
+        PLAYBACK_TIME_CHANGED,
+        QUEUE_CHANGED,
+    ];
+}
+
+/// Emits player events with the payload documented on each event constant.
+#[derive(Debug)]
+pub(crate) struct PlayerEmitter<R: Runtime> {
+    app: AppHandle<R>,
+    throttle: Option<Arc<StateThrottle>>,
+}
+
+impl<R: Runtime> Clone for PlayerEmitter<R> {
+    fn clone(&self) -> Self {
+        Self {
+            app: self.app.clone(),
+            throttle: self.throttle.clone(),
+        }
+    }
+}
+
+// The desktop backend only reports queue and state changes.
+#[cfg_attr(not(any(mobile, feature = "mock")), allow(dead_code))]
+impl<R: Runtime> PlayerEmitter<R> {
+    pub(crate) fn new(app: AppHandle<R>) -> Self {
+        let throttle = app
+            .try_state::<Arc<StateThrottle>>()
+            .map(|throttle| Arc::clone(&throttle));
+        Self { app, throttle }
+    }
+
+    /// `PLAYER_ADAPTER_EVENTS.INITIALIZED`, without a payload.
+    pub(crate) fn initialized(&self) {
+        self.emit(PLAYER_ADAPTER_EVENTS_INITIALIZED, ());
+    }
+
+    /// `PLAYER_ADAPTER_EVENTS.AUTHORIZATION_STATUS_CHANGE` with an
+    /// [`AuthorizationStatusResponse`].
+    pub(crate) fn authorization_status_change(&self, status: AuthorizationStatus) {
+        self.emit(
+            PLAYER_ADAPTER_EVENTS_AUTHORIZATION_STATUS_CHANGE,
+            AuthorizationStatusResponse { status },
+        );
+    }
+
+    /// `PLAYER_ADAPTER_EVENTS.STATE_UPDATE` with a [`StateUpdateEvent`], throttled when
+    /// `eventThrottleMs` is configured.
+    pub(crate) fn state_update(&self, state: StateUpdateEvent) {
+        let Some(throttle) = &self.throttle else {
+            return self.emit(PLAYER_ADAPTER_EVENTS_STATE_UPDATE, state);
+        };
+        match throttle.offer(state, Instant::now()) {
+            Throttled::Emit(state) => self.emit(PLAYER_ADAPTER_EVENTS_STATE_UPDATE, *state),
+            Throttled::FlushAfter(delay) => {
+                let emitter = self.clone();
+                let throttle = throttle.clone();
+                tauri::async_runtime::spawn(async move {
+                    tokio::time::sleep(delay).await;
+                    if let Some(state) = throttle.flush(Instant::now()) {
+                        emitter.emit(PLAYER_ADAPTER_EVENTS_STATE_UPDATE, state);
+                    }
+                });
+            }
+            Throttled::Coalesced => {}
+        }
+    }
+
+    /// `PLAYER_ADAPTER_EVENTS.TRACK_CHANGE` with a [`TrackChangeEvent`].
+    pub(crate) fn track_change(&self, track: MusicKitTrack) {
+        self.emit(
+            PLAYER_ADAPTER_EVENTS_TRACK_CHANGE,
+            TrackChangeEvent { track },
+        );
+    }
+
+    /// `PLAYER_ADAPTER_EVENTS.QUEUE_UPDATE` with a [`QueueUpdateEvent`].
+    pub(crate) fn queue_update(&self, event: QueueUpdateEvent) {
+        self.emit(PLAYER_ADAPTER_EVENTS_QUEUE_UPDATE, event);
+    }
+
+    /// `PLAYER_ADAPTER_EVENTS.QUEUE_RESTORED` with a [`QueueRestoredEvent`].
+    pub(crate) fn queue_restored(&self, event: QueueRestoredEvent) {
+        self.emit(PLAYER_ADAPTER_EVENTS_QUEUE_RESTORED, event);
+    }
+
+    /// `PLAYER_ADAPTER_EVENTS.ERROR` with an [`ErrorEvent`].
+    pub(crate) fn error(&self, event: ErrorEvent) {
+        self.emit(PLAYER_ADAPTER_EVENTS_ERROR, event);
+    }
+
+    /// Re-emits a [`native`] trigger as the matching `PLAYER_ADAPTER_EVENTS_*` events.
+    /// The state is read back from `backend`, so every event carries the same shape
+    /// whichever platform fired it.
+    pub(crate) fn forward_native(
+        &self,
+        trigger: &str,
+        backend: &impl MusicKitBackend,
+    ) -> Result<()> {
+        match trigger {
+            native::TRACK_CHANGED => {
+                if let Some(track) = backend.get_current_track()? {
+                    self.track_change(track);
+                }
+                self.state_update(backend.get_playback_state()?);
+            }
+            native::PLAYBACK_STATE_CHANGED | native::PLAYBACK_TIME_CHANGED => {
+                self.state_update(backend.get_playback_state()?);
+            }
+            native::QUEUE_CHANGED => {
+                self.queue_update(Queue::from(backend.get_queue()?).to_event());
+            }
+            _ => {}
+        }
+        Ok(())
+    }
+
+    fn emit<S: Serialize + Clone>(&self, event: &str, payload: S) {
+        if let Err(error) = self.app.emit(event, payload) {
+            log::warn!("failed to emit {event}: {error}");
+        }
+    }
+}
+
+#[cfg(all(test, feature = "mock"))]
+mod tests {
+    use std::{sync::mpsc, time::Duration};
+
+    use tauri::{
+        test::{mock_app, MockRuntime},
+        App, Listener,
+    };
+
+    use super::*;
+    use crate::mock::MockMusicKit;
+
+    const FORWARDED_EVENTS: [&str; 4] = [
+        PLAYER_ADAPTER_EVENTS_STATE_UPDATE,
+        PLAYER_ADAPTER_EVENTS_TRACK_CHANGE,
+        PLAYER_ADAPTER_EVENTS_QUEUE_UPDATE,
+        PLAYER_ADAPTER_EVENTS_INITIALIZED,
+    ];
+
+    fn track(id: &str) -> MusicKitTrack {
+        MusicKitTrack {
+            id: id.to_string(),
+            duration: 180.0,
+            ..Default::default()
+        }
+    }
+
+    /// Forwards `trigger` from a mock playing the second of three tracks and returns the
+    /// events it was normalized into, with their payloads.
+    fn forward(trigger: &str) -> Vec<(&'static str, String)> {
+        let app: App<MockRuntime> = mock_app();
+        let mock = MockMusicKit::new(app.handle().clone());
+        mock.set_queue(vec![track("a"), track("b"), track("c")], true, 1)
+            .unwrap();
+
+        let (sender, receiver) = mpsc::channel();
+        for event in FORWARDED_EVENTS {
+            let sender = sender.clone();
+            app.listen_any(event, move |emitted| {
+                let _ = sender.send((event, emitted.payload().to_string()));
+            });
+        }
+        let emitter = PlayerEmitter::new(app.handle().clone());
+        emitter.forward_native(trigger, &mock).unwrap();
+        // Events arrive in order, so this marks the end of the forwarded ones.
+        emitter.initialized();
+
+        let mut events = Vec::new();
+        loop {
+            let event = receiver.recv_timeout(Duration::from_secs(1)).unwrap();
+            if event.0 == PLAYER_ADAPTER_EVENTS_INITIALIZED {
+                return events;
+            }
+            events.push(event);
+        }
+    }
+
+    fn names(events: &[(&'static str, String)]) -> Vec<&'static str> {
+        events.iter().map(|(event, _)| *event).collect()
+    }
+
+    fn state(payload: &str) -> StateUpdateEvent {
+        let state: StateUpdateEvent = serde_json::from_str(payload).unwrap();
+        assert_eq!(state.current_track.as_ref().unwrap().id, "b");
+        assert_eq!(state.queue_position, 1);
+        assert!(state.playing);
+        state
+    }
+
+    #[test]
+    fn track_changes_emit_the_new_track_then_the_state() {
+        let events = forward(native::TRACK_CHANGED);
+        assert_eq!(
+            names(&events),
+            [
+                PLAYER_ADAPTER_EVENTS_TRACK_CHANGE,
+                PLAYER_ADAPTER_EVENTS_STATE_UPDATE
+            ]
+        );
+        let change: TrackChangeEvent = serde_json::from_str(&events[0].1).unwrap();
+        assert_eq!(change.track.id, "b");
+        state(&events[1].1);
+    }
+
+    #[test]
+    fn playback_state_and_time_changes_emit_the_state() {
+        for trigger in [
+            native::PLAYBACK_STATE_CHANGED,
+            native::PLAYBACK_TIME_CHANGED,
+        ] {
+            let events = forward(trigger);
+            assert_eq!(
+                names(&events),
+                [PLAYER_ADAPTER_EVENTS_STATE_UPDATE],
+                "{trigger}"
+            );
+            state(&events[0].1);
+        }
+    }
+
+    #[test]
+    fn queue_changes_emit_the_queue() {
+        let events = forward(native::QUEUE_CHANGED);
+        assert_eq!(names(&events), [PLAYER_ADAPTER_EVENTS_QUEUE_UPDATE]);
+        let update: QueueUpdateEvent = serde_json::from_str(&events[0].1).unwrap();
+        let ids: Vec<_> = update.items.iter().map(|track| track.id.as_str()).collect();
+        assert_eq!(ids, ["a", "b", "c"]);
+        assert_eq!(update.position, 1);
+    }
+
+    #[test]
+    fn unknown_triggers_are_ignored() {
+        assert!(forward("musickit-volume-changed").is_empty());
+    }
+}
@@ -1,4 +1,5 @@
-use serde::Serialize;
+use serde::{ser::SerializeStruct, Deserialize, Serialize};
+use serde_json::{json, Value};
 use thiserror::Error;
 
 #[derive(Debug, Error)]
@@ -15,16 +16,209 @@ pub enum Error {
     PlatformNotSupported,
     #[error("MusicKit error: {0}")]
     MusicKitError(String),
+    #[error("{message}")]
+    Native {
+        code: ErrorCode,
+        message: String,
+        details: Option<Value>,
+    },
     #[error("Tauri error: {0}")]
     Tauri(String),
+    #[error("Queue position {position} is out of range for {len} items")]
+    QueuePositionOutOfRange { position: usize, len: usize },
+    #[error("Track {0} is not in the queue")]
+    TrackNotInQueue(String),
+    #[error("Nothing to undo")]
+    NothingToUndo,
+    #[error("Nothing to redo")]
+    NothingToRedo,
+    #[error("IO error: {0}")]
+    Io(String),
+    #[error("Invalid stored queue: {0}")]
+    InvalidStoredQueue(String),
+    #[error("Invalid plugin configuration: {0}")]
+    InvalidConfig(String),
+    #[error("The `{option}` option requires the `{feature}` feature")]
+    FeatureNotEnabled {
+        option: &'static str,
+        feature: &'static str,
+    },
+    #[error("Developer token is not set")]
+    MissingDeveloperToken,
+    #[cfg(feature = "jwt")]
+    #[error("Developer token error: {0}")]
+    DeveloperToken(String),
+    #[cfg(feature = "api")]
+    #[error("HTTP error: {0}")]
+    Http(String),
+    #[cfg(feature = "api")]
+    #[error("Apple Music API error ({status}): {message}")]
+    Api { status: u16, message: String },
+    #[cfg(feature = "api")]
+    #[error("Not an Apple Music artwork URL: {0}")]
+    InvalidArtworkUrl(String),
+    #[cfg(feature = "api")]
+    #[error("Invalid Apple Music API response: {0}")]
+    InvalidResponse(String),
+    #[cfg(feature = "api")]
+    #[error("{kind} {id} not found")]
+    NotFound { kind: String, id: String },
 }
 
+/// A stable classification of an [`Error`] that callers can branch on.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum ErrorCode {
+    NotInitialized,
+    NotAuthorized,
+    SubscriptionRequired,
+    ContentUnavailable,
+    Network,
+    RateLimited,
+    InvalidArgument,
+    InvalidState,
+    NotSupported,
+    Configuration,
+    Internal,
+    Unknown,
+}
+
+impl ErrorCode {
+    const ALL: [ErrorCode; 12] = [
+        Self::NotInitialized,
+        Self::NotAuthorized,
+        Self::SubscriptionRequired,
+        Self::ContentUnavailable,
+        Self::Network,
+        Self::RateLimited,
+        Self::InvalidArgument,
+        Self::InvalidState,
+        Self::NotSupported,
+        Self::Configuration,
+        Self::Internal,
+        Self::Unknown,
+    ];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::NotInitialized => "NOT_INITIALIZED",
+            Self::NotAuthorized => "NOT_AUTHORIZED",
+            Self::SubscriptionRequired => "SUBSCRIPTION_REQUIRED",
+            Self::ContentUnavailable => "CONTENT_UNAVAILABLE",
+            Self::Network => "NETWORK",
+            Self::RateLimited => "RATE_LIMITED",
+            Self::InvalidArgument => "INVALID_ARGUMENT",
+            Self::InvalidState => "INVALID_STATE",
+            Self::NotSupported => "NOT_SUPPORTED",
+            Self::Configuration => "CONFIGURATION",
+            Self::Internal => "INTERNAL",
+            Self::Unknown => "UNKNOWN",
+        }
+    }
+
+    /// Parses a code as serialized, such as the `code` a native reject carries.
+    pub fn parse(code: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|known| known.as_str() == code)
+    }
+
+    /// Guesses the code of a native failure from its message.
+    pub fn classify(message: &str) -> Self {
+        let message = message.to_ascii_lowercase();
+        let mentions = |needles: &[&str]| needles.iter().any(|needle| message.contains(needle));
+        if mentions(&["subscription", "capability"]) {
+            Self::SubscriptionRequired
+        } else if mentions(&["rate limit", "too many requests"]) {
+            Self::RateLimited
+        } else if mentions(&["network", "offline", "internet", "timed out", "connection"]) {
+            Self::Network
+        } else if mentions(&["not authorized", "unauthorized", "denied", "token"]) {
+            Self::NotAuthorized
+        } else if mentions(&["not found", "unavailable", "not available"]) {
+            Self::ContentUnavailable
+        } else if mentions(&["invalid", "unsupported", "failed to parse arguments"]) {
+            Self::InvalidArgument
+        } else {
+            Self::Unknown
+        }
+    }
+}
+
+impl Error {
+    /// The stable code of this error.
+    pub fn code(&self) -> ErrorCode {
+        match self {
+            Error::NotInitialized => ErrorCode::NotInitialized,
+            Error::AlreadyInitialized | Error::NothingToUndo | Error::NothingToRedo => {
+                ErrorCode::InvalidState
+            }
+            Error::InvalidTrackIdentifier(_)
+            | Error::InvalidTrackFormat(_)
+            | Error::QueuePositionOutOfRange { .. }
+            | Error::TrackNotInQueue(_) => ErrorCode::InvalidArgument,
+            Error::PlatformNotSupported => ErrorCode::NotSupported,
+            Error::MusicKitError(message) => ErrorCode::classify(message),
+            Error::Native { code, .. } => *code,
+            Error::Tauri(_) | Error::Io(_) | Error::InvalidStoredQueue(_) => ErrorCode::Internal,
+            Error::InvalidConfig(_) | Error::FeatureNotEnabled { .. } => ErrorCode::Configuration,
+            Error::MissingDeveloperToken => ErrorCode::NotAuthorized,
+            #[cfg(feature = "jwt")]
+            Error::DeveloperToken(_) => ErrorCode::Configuration,
+            #[cfg(feature = "api")]
+            Error::Http(_) => ErrorCode::Network,
+            #[cfg(feature = "api")]
+            Error::Api { status, .. } => match status {
+                400 => ErrorCode::InvalidArgument,
+                401 | 403 => ErrorCode::NotAuthorized,
+                404 => ErrorCode::ContentUnavailable,
+                429 => ErrorCode::RateLimited,
+                500..=599 => ErrorCode::Network,
+                _ => ErrorCode::Unknown,
+            },
+            #[cfg(feature = "api")]
+            Error::InvalidArtworkUrl(_) => ErrorCode::InvalidArgument,
+            #[cfg(feature = "api")]
+            Error::InvalidResponse(_) => ErrorCode::Internal,
+            #[cfg(feature = "api")]
+            Error::NotFound { .. } => ErrorCode::ContentUnavailable,
+        }
+    }
+
+    /// Structured context for the error, when there is any.
+    pub fn details(&self) -> Option<Value> {
+        match self {
+            Error::QueuePositionOutOfRange { position, len } => {
+                Some(json!({ "position": position, "len": len }))
+            }
+            Error::TrackNotInQueue(track_id) => Some(json!({ "trackId": track_id })),
+            Error::FeatureNotEnabled { option, feature } => {
+                Some(json!({ "option": option, "feature": feature }))
+            }
+            Error::Native { details, .. } => details.clone(),
+            #[cfg(feature = "api")]
+            Error::Api { status, .. } => Some(json!({ "status": status })),
+            #[cfg(feature = "api")]
+            Error::InvalidArtworkUrl(url) => Some(json!({ "url": url })),
+            #[cfg(feature = "api")]
+            Error::NotFound { kind, id } => Some(json!({ "kind": kind, "id": id })),
+            _ => None,
+        }
+    }
+}
+
+/// Serialized as `{ code, message, details? }`.
 impl Serialize for Error {
     fn serialize<S>(&self, serializer: S) -> std::result::Result<S::Ok, S::Error>
     where
         S: serde::Serializer,
     {
-        serializer.serialize_str(&self.to_string())
+        let details = self.details();
+        let mut error = serializer.serialize_struct("Error", 2 + usize::from(details.is_some()))?;
+        error.serialize_field("code", &self.code())?;
+        error.serialize_field("message", &self.to_string())?;
+        if let Some(details) = details {
+            error.serialize_field("details", &details)?;
+        }
+        error.end()
     }
 }
 
@@ -34,11 +228,188 @@ impl From<tauri::Error> for Error {
     }
 }
 
+#[cfg(feature = "api")]
+impl From<reqwest::Error> for Error {
+    fn from(error: reqwest::Error) -> Self {
+        Error::Http(error.to_string())
+    }
+}
+
+/// Rejections from the native plugin become [`Error::Native`], keeping their `code` when
+/// it is one of ours and classifying their message otherwise.
 #[cfg(mobile)]
 impl From<tauri::plugin::mobile::PluginInvokeError> for Error {
     fn from(error: tauri::plugin::mobile::PluginInvokeError) -> Self {
-        Error::Tauri(error.to_string())
+        use tauri::plugin::mobile::PluginInvokeError;
+
+        let response = match error {
+            PluginInvokeError::InvokeRejected(response) => response,
+            error => return Error::Tauri(error.to_string()),
+        };
+        let message = response.message.unwrap_or_default();
+        let known = response.code.as_deref().and_then(ErrorCode::parse);
+        Error::Native {
+            code: known.unwrap_or_else(|| ErrorCode::classify(&message)),
+            details: response
+                .code
+                .filter(|_| known.is_none())
+                .map(|code| json!({ "nativeCode": code })),
+            message,
+        }
     }
 }
 
 pub type Result<T> = std::result::Result<T, Error>;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn codes_parse_from_their_serialized_form() {
+        for code in ErrorCode::ALL {
+            assert_eq!(serde_json::to_value(code).unwrap(), json!(code.as_str()));
+            assert_eq!(ErrorCode::parse(code.as_str()), Some(code));
+        }
+        assert_eq!(ErrorCode::parse("notAuthorized"), None);
+        assert_eq!(ErrorCode::parse(""), None);
+    }
+
+    #[test]
+    fn native_messages_are_classified() {
+        for (message, code) in [
+            (
+                "The user has no active subscription",
+                ErrorCode::SubscriptionRequired,
+            ),
+            ("Missing capability", ErrorCode::SubscriptionRequired),
+            ("Too Many Requests", ErrorCode::RateLimited),
+            (
+                "The Internet connection appears to be offline.",
+                ErrorCode::Network,
+            ),
+            ("The request timed out.", ErrorCode::Network),
+            ("Permission denied", ErrorCode::NotAuthorized),
+            ("Developer token not set.", ErrorCode::NotAuthorized),
+            ("Track not found", ErrorCode::ContentUnavailable),
+            (
+                "This song is not available in your region",
+                ErrorCode::ContentUnavailable,
+            ),
+            (
+                "Unsupported shuffle mode: sideways",
+                ErrorCode::InvalidArgument,
+            ),
+            ("Failed to parse arguments", ErrorCode::InvalidArgument),
+            ("Something went wrong", ErrorCode::Unknown),
+            ("", ErrorCode::Unknown),
+        ] {
+            assert_eq!(ErrorCode::classify(message), code, "{message}");
+        }
+    }
+
+    #[test]
+    fn errors_map_onto_codes() {
+        for (error, code) in [
+            (Error::NotInitialized, ErrorCode::NotInitialized),
+            (Error::NothingToUndo, ErrorCode::InvalidState),
+            (
+                Error::QueuePositionOutOfRange {
+                    position: 3,
+                    len: 2,
+                },
+                ErrorCode::InvalidArgument,
+            ),
+            (Error::PlatformNotSupported, ErrorCode::NotSupported),
+            (
+                Error::MusicKitError("network is down".to_string()),
+                ErrorCode::Network,
+            ),
+            (Error::Io("disk full".to_string()), ErrorCode::Internal),
+            (
+                Error::InvalidConfig("bad".to_string()),
+                ErrorCode::Configuration,
+            ),
+            (Error::MissingDeveloperToken, ErrorCode::NotAuthorized),
+        ] {
+            assert_eq!(error.code(), code, "{error}");
+        }
+    }
+
+    #[cfg(feature = "api")]
+    #[test]
+    fn api_statuses_map_onto_codes() {
+        let api = |status| Error::Api {
+            status,
+            message: String::new(),
+        };
+        assert_eq!(api(400).code(), ErrorCode::InvalidArgument);
+        assert_eq!(api(401).code(), ErrorCode::NotAuthorized);
+        assert_eq!(api(403).code(), ErrorCode::NotAuthorized);
+        assert_eq!(api(404).code(), ErrorCode::ContentUnavailable);
+        assert_eq!(api(429).code(), ErrorCode::RateLimited);
+        assert_eq!(api(503).code(), ErrorCode::Network);
+        assert_eq!(api(418).code(), ErrorCode::Unknown);
+    }
+
+    #[cfg(feature = "api")]
+    #[test]
+    fn api_responses_that_do_not_fit_are_not_network_errors() {
+        let error = Error::InvalidResponse("invalid tracks relationship".to_string());
+        assert_eq!(error.code(), ErrorCode::Internal);
+
+        let error = Error::NotFound {
+            kind: "albums".to_string(),
+            id: "1".to_string(),
+        };
+        assert_eq!(
+            serde_json::to_value(&error).unwrap(),
+            json!({
+                "code": "CONTENT_UNAVAILABLE",
+                "message": "albums 1 not found",
+                "details": { "kind": "albums", "id": "1" },
+            })
+        );
+    }
+
+    #[test]
+    fn errors_serialize_as_code_message_and_details() {
+        let error = Error::QueuePositionOutOfRange {
+            position: 5,
+            len: 2,
+        };
+        assert_eq!(
+            serde_json::to_value(&error).unwrap(),
+            json!({
+                "code": "INVALID_ARGUMENT",
+                "message": "Queue position 5 is out of range for 2 items",
+                "details": { "position": 5, "len": 2 },
+            })
+        );
+    }
+
+    #[test]
+    fn details_are_left_out_when_there_are_none() {
+        assert_eq!(
+            serde_json::to_value(Error::NothingToRedo).unwrap(),
+            json!({ "code": "INVALID_STATE", "message": "Nothing to redo" })
+        );
+    }
+
+    #[test]
+    fn native_errors_keep_their_code_and_details() {
+        let error = Error::Native {
+            code: ErrorCode::ContentUnavailable,
+            message: "Track not found".to_string(),
+            details: Some(json!({ "nativeCode": "E42" })),
+        };
+        assert_eq!(
+            serde_json::to_value(&error).unwrap(),
+            json!({
+                "code": "CONTENT_UNAVAILABLE",
+                "message": "Track not found",
+                "details": { "nativeCode": "E42" },
+            })
+        );
+    }
+}
@@ -1,7 +1,20 @@
-/// Event constants for MusicKit plugin
+//! Event constants for MusicKit plugin
+//!
+//! Each event is emitted on the app, so webview `listen` calls and Rust `app.listen`
+//! handlers receive the same name and payload.
+
+/// MusicKit finished initializing. No payload.
 pub const PLAYER_ADAPTER_EVENTS_INITIALIZED: &str = "PLAYER_ADAPTER_EVENTS.INITIALIZED";
-pub const PLAYER_ADAPTER_EVENTS_AUTHORIZATION_STATUS_CHANGE: &str = "PLAYER_ADAPTER_EVENTS.AUTHORIZATION_STATUS_CHANGE";
+/// The authorization status changed. Payload: [`crate::AuthorizationStatusResponse`].
+pub const PLAYER_ADAPTER_EVENTS_AUTHORIZATION_STATUS_CHANGE: &str =
+    "PLAYER_ADAPTER_EVENTS.AUTHORIZATION_STATUS_CHANGE";
+/// Playback state or time changed. Payload: [`crate::StateUpdateEvent`].
 pub const PLAYER_ADAPTER_EVENTS_STATE_UPDATE: &str = "PLAYER_ADAPTER_EVENTS.STATE_UPDATE";
+/// The queue changed. Payload: [`crate::QueueUpdateEvent`].
 pub const PLAYER_ADAPTER_EVENTS_QUEUE_UPDATE: &str = "PLAYER_ADAPTER_EVENTS.QUEUE_UPDATE";
+/// Another track became current. Payload: [`crate::TrackChangeEvent`].
 pub const PLAYER_ADAPTER_EVENTS_TRACK_CHANGE: &str = "PLAYER_ADAPTER_EVENTS.TRACK_CHANGE";
-pub const PLAYER_ADAPTER_EVENTS_ERROR: &str = "PLAYER_ADAPTER_EVENTS.ERROR"; 
\ No newline at end of file
+/// The player reported an error. Payload: [`crate::ErrorEvent`].
+pub const PLAYER_ADAPTER_EVENTS_ERROR: &str = "PLAYER_ADAPTER_EVENTS.ERROR";
+/// The saved queue was restored. Payload: [`crate::QueueRestoredEvent`].
+pub const PLAYER_ADAPTER_EVENTS_QUEUE_RESTORED: &str = "PLAYER_ADAPTER_EVENTS.QUEUE_RESTORED";
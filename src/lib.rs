@@ -1,84 +1,991 @@
+use std::{
+    ops::Deref,
+    sync::{Arc, Mutex, MutexGuard, PoisonError},
+    time::Duration,
+};
+
 use tauri::{
-    plugin::{Builder, TauriPlugin},
-    Manager, Runtime,
+    plugin::{self, TauriPlugin},
+    AppHandle, Listener, Manager, Runtime,
 };
+use tokio::sync::broadcast;
 
+#[cfg(feature = "api")]
+pub mod api;
+mod artwork;
+#[cfg(feature = "api")]
+pub mod artwork_cache;
+mod backend;
+mod clock;
 mod commands;
+mod config;
+mod emitter;
 mod error;
 mod events;
+#[cfg(feature = "jwt")]
+pub mod jwt;
 mod models;
+pub mod persistence;
+pub mod queue;
+mod subscription;
+mod throttle;
 
 #[cfg(desktop)]
 mod desktop;
 #[cfg(mobile)]
 mod mobile;
+#[cfg(feature = "mock")]
+mod mock;
 
-pub use error::{Error, Result};
+pub use artwork::{Artwork, ArtworkFormat, MAX_ARTWORK_SIZE};
+pub use backend::MusicKitBackend;
+pub use clock::PlaybackClock;
+pub use config::{ArtworkCacheConfig, BackendKind, Config, DeveloperKeyConfig};
+pub use error::{Error, ErrorCode, Result};
 pub use events::*;
 pub use models::*;
+pub use subscription::{PlayerEvent, PlayerEventStream, DEFAULT_EVENT_CAPACITY};
 
-#[cfg(desktop)]
-use desktop::MusicKitPlugin;
-#[cfg(mobile)]
-use mobile::MusicKitPlugin;
+#[cfg(feature = "mock")]
+pub use mock::MockMusicKit;
 
 #[cfg(target_os = "ios")]
 tauri::ios_plugin_binding!(init_plugin_apple_music_kit);
 
+/// Access to the MusicKit APIs through the backend the plugin was initialized with.
+pub struct MusicKit {
+    backend: Arc<dyn MusicKitBackend>,
+    config: Config,
+    queue_history: Mutex<queue::QueueHistory>,
+    persistence: Option<Arc<persistence::QueuePersistence>>,
+    events: broadcast::Sender<PlayerEvent>,
+    clock: Arc<Mutex<PlaybackClock>>,
+    #[cfg(feature = "api")]
+    suggestions: api::SuggestionDebouncer,
+    #[cfg(feature = "api")]
+    artwork_cache: Option<Arc<artwork_cache::ArtworkCache>>,
+    #[cfg(feature = "jwt")]
+    token_refresher: Mutex<Option<jwt::TokenRefresher>>,
+}
+
+impl MusicKit {
+    fn new(backend: Arc<dyn MusicKitBackend>, config: Config) -> Self {
+        Self {
+            backend,
+            config,
+            queue_history: Default::default(),
+            persistence: None,
+            events: broadcast::channel(subscription::DEFAULT_EVENT_CAPACITY).0,
+            clock: Default::default(),
+            #[cfg(feature = "api")]
+            suggestions: Default::default(),
+            #[cfg(feature = "api")]
+            artwork_cache: None,
+            #[cfg(feature = "jwt")]
+            token_refresher: Default::default(),
+        }
+    }
+
+    /// Initializes the backend and, the first time, restores the saved queue when
+    /// `restoreQueue` is configured.
+    pub fn initialize(&self) -> Result<()> {
+        self.backend.initialize()?;
+        if let Some(persistence) = &self.persistence {
+            persistence.restore(self.backend.as_ref())?;
+        }
+        Ok(())
+    }
+
+    /// Returns a stream of the player events emitted from now on.
+    ///
+    /// Every call creates an independent subscriber. One that falls too far behind
+    /// receives a [`PlayerEvent::Lagged`] in place of the events it missed.
+    pub fn subscribe(&self) -> PlayerEventStream {
+        PlayerEventStream::new(self.events.subscribe())
+    }
+
+    /// A clock anchored on the latest `STATE_UPDATE`, for reading the playback position
+    /// between updates without querying the backend.
+    pub fn playback_clock(&self) -> PlaybackClock {
+        *self.clock.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    /// The configuration the plugin was initialized with.
+    pub fn config(&self) -> &Config {
+        &self.config
+    }
+
+    /// The current developer token.
+    ///
+    /// With a [`jwt::DeveloperTokenMinter`] installed this is the minter's cached token,
+    /// re-minted when it is about to expire. Otherwise it is whatever the backend holds,
+    /// falling back to the configured `developerToken`.
+    pub fn developer_token(&self) -> Result<Option<String>> {
+        #[cfg(feature = "jwt")]
+        {
+            let refresher = self
+                .token_refresher
+                .lock()
+                .unwrap_or_else(PoisonError::into_inner);
+            if let Some(refresher) = refresher.as_ref() {
+                let (token, minted) = refresher.minter().token()?;
+                if minted {
+                    self.backend.set_developer_token(token.clone())?;
+                }
+                return Ok(Some(token));
+            }
+        }
+        let token = self
+            .backend
+            .get_developer_token()?
+            .filter(|token| !token.is_empty());
+        Ok(token.or_else(|| self.config.developer_token.clone()))
+    }
+
+    /// Mints developer tokens with `minter` from now on, handing each one to the backend
+    /// and re-minting ahead of expiry. Replaces any previously installed minter.
+    #[cfg(feature = "jwt")]
+    pub fn set_developer_token_minter(&self, minter: jwt::DeveloperTokenMinter) -> Result<()> {
+        let refresher = jwt::TokenRefresher::start(minter, self.backend.clone())?;
+        *self
+            .token_refresher
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner) = Some(refresher);
+        Ok(())
+    }
+
+    /// Creates an Apple Music API client from the current tokens and storefront.
+    #[cfg(feature = "api")]
+    pub fn api_client(&self) -> Result<api::AppleMusicClient> {
+        let developer_token = self
+            .developer_token()?
+            .filter(|token| !token.is_empty())
+            .ok_or(Error::MissingDeveloperToken)?;
+        let mut client = api::AppleMusicClient::new(developer_token);
+        if let Some(storefront) = &self.config.storefront {
+            client = client.with_storefront(storefront.clone());
+        }
+        client.with_backend_session(self.backend.as_ref())
+    }
+
+    /// The cache behind the `musickit-artwork` URI scheme, when `artworkCache` is
+    /// configured.
+    #[cfg(feature = "api")]
+    pub fn artwork_cache(&self) -> Option<&Arc<artwork_cache::ArtworkCache>> {
+        self.artwork_cache.as_ref()
+    }
+
+    /// Fetches type-ahead suggestions for a partial search term.
+    ///
+    /// Calls are debounced, and a call superseded by a newer one before its request
+    /// completes resolves to `Ok(None)`.
+    #[cfg(feature = "api")]
+    pub async fn get_search_suggestions(
+        &self,
+        request: api::SearchSuggestionsRequest,
+    ) -> Result<Option<api::SearchSuggestions>> {
+        let client = self.api_client()?;
+        self.suggestions
+            .run(client.search_suggestions(&request))
+            .await
+            .transpose()
+    }
+}
+
+/// Queue changes, recorded for undo and redo.
+impl MusicKit {
+    pub fn set_queue(
+        &self,
+        tracks: Vec<MusicKitTrack>,
+        start_playing: bool,
+        start_position: usize,
+    ) -> Result<QueueResponse> {
+        self.record_queue_change(|backend| backend.set_queue(tracks, start_playing, start_position))
+    }
+
+    /// Replaces the queue with a whole album, playlist, artist's top songs or station
+    /// and starts playing it.
+    ///
+    /// The container is resolved through the Apple Music API, every page of its
+    /// tracks included, and handed to the backend as one [`QueueContainer`].
+    /// `start_index` is an index into the container's own track order; stations have
+    /// no tracks to index and start wherever the station does.
+    #[cfg(feature = "api")]
+    pub async fn set_queue_from_container(
+        &self,
+        kind: ContainerKind,
+        id: String,
+        start_index: usize,
+        shuffle: bool,
+    ) -> Result<QueueResponse> {
+        let client = self.api_client()?;
+        self.queue_container(&client, kind, id, start_index, shuffle)
+            .await
+    }
+
+    /// Resolves a container through `client` and queues it.
+    #[cfg(feature = "api")]
+    async fn queue_container(
+        &self,
+        client: &api::AppleMusicClient,
+        kind: ContainerKind,
+        id: String,
+        start_index: usize,
+        shuffle: bool,
+    ) -> Result<QueueResponse> {
+        let request = api::DetailRequest::new(id.clone());
+        let tracks = match kind {
+            ContainerKind::Album => client.get_album(&request).await?.tracks,
+            ContainerKind::Playlist => client.get_playlist(&request).await?.tracks,
+            ContainerKind::Artist => client.get_top_songs(&request).await?,
+            ContainerKind::Station => Vec::new(),
+        };
+        if kind != ContainerKind::Station && start_index >= tracks.len() {
+            return Err(Error::QueuePositionOutOfRange {
+                position: start_index,
+                len: tracks.len(),
+            });
+        }
+        let container = QueueContainer {
+            kind,
+            id,
+            tracks,
+            start_index,
+            shuffle,
+        };
+        self.record_queue_change(|backend| backend.set_queue_from_container(container))
+    }
+
+    pub fn update_queue(&self, tracks: Vec<MusicKitTrack>) -> Result<QueueResponse> {
+        self.record_queue_change(|backend| backend.update_queue(tracks))
+    }
+
+    pub fn insert_track_at_position(
+        &self,
+        track: MusicKitTrack,
+        position: usize,
+    ) -> Result<QueueResponse> {
+        self.record_queue_change(|backend| backend.insert_track_at_position(track, position))
+    }
+
+    pub fn insert_tracks_at_position(
+        &self,
+        tracks: Vec<MusicKitTrack>,
+        position: usize,
+    ) -> Result<QueueResponse> {
+        self.record_queue_change(|backend| backend.insert_tracks_at_position(tracks, position))
+    }
+
+    pub fn remove_track_from_queue(&self, track_id: String) -> Result<QueueResponse> {
+        self.record_queue_change(|backend| backend.remove_track_from_queue(track_id))
+    }
+
+    pub fn insert_track_next(&self, track: MusicKitTrack) -> Result<QueueResponse> {
+        self.record_queue_change(|backend| backend.insert_track_next(track))
+    }
+
+    pub fn insert_track_last(&self, track: MusicKitTrack) -> Result<QueueResponse> {
+        self.record_queue_change(|backend| backend.insert_track_last(track))
+    }
+
+    pub fn append_tracks_to_queue(&self, tracks: Vec<MusicKitTrack>) -> Result<QueueResponse> {
+        self.record_queue_change(|backend| backend.append_tracks_to_queue(tracks))
+    }
+
+    /// Removes the item at `position`, which is unambiguous when a song is queued twice.
+    pub fn remove_track_at_position(&self, position: usize) -> Result<QueueResponse> {
+        self.record_queue_change(|backend| backend.remove_track_at_position(position))
+    }
+
+    /// Removes the items from `start` up to, but not including, `end`.
+    pub fn remove_range(&self, start: usize, end: usize) -> Result<QueueResponse> {
+        self.record_queue_change(|backend| backend.remove_range(start, end))
+    }
+
+    pub fn clear_queue(&self) -> Result<QueueResponse> {
+        self.record_queue_change(|backend| backend.clear_queue())
+    }
+
+    /// Moves the item at `from` to index `to`, keeping the current item playing.
+    pub fn move_queue_item(&self, from: usize, to: usize) -> Result<QueueResponse> {
+        self.record_queue_change(|backend| backend.move_queue_item(from, to))
+    }
+
+    /// Moves the items at `indices` into one block starting at `to`, counted in the queue
+    /// without the moved items, keeping the current item playing.
+    pub fn move_queue_items(&self, indices: Vec<usize>, to: usize) -> Result<QueueResponse> {
+        self.record_queue_change(|backend| backend.move_queue_items(indices, to))
+    }
+
+    /// Restores the queue and cursor from before the last change.
+    ///
+    /// The backend emits `QUEUE_UPDATE` for the restored queue. Fails with
+    /// [`Error::NothingToUndo`] when there is nothing to undo.
+    pub fn undo_queue_change(&self) -> Result<QueueResponse> {
+        let current = queue::Queue::from(self.backend.get_queue()?);
+        let previous = self
+            .queue_history()
+            .undo(current)
+            .ok_or(Error::NothingToUndo)?;
+        let response = self.restore_queue(previous.clone());
+        if response.is_err() {
+            // Put the history back the way it was.
+            self.queue_history().redo(previous);
+        }
+        response
+    }
+
+    /// Reapplies the last change [`MusicKit::undo_queue_change`] reverted.
+    pub fn redo_queue_change(&self) -> Result<QueueResponse> {
+        let current = queue::Queue::from(self.backend.get_queue()?);
+        let next = self
+            .queue_history()
+            .redo(current)
+            .ok_or(Error::NothingToRedo)?;
+        let response = self.restore_queue(next.clone());
+        if response.is_err() {
+            self.queue_history().undo(next);
+        }
+        response
+    }
+
+    fn queue_history(&self) -> MutexGuard<'_, queue::QueueHistory> {
+        self.queue_history
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+    }
+
+    /// Runs a queue operation and records the queue it replaced when it succeeds.
+    fn record_queue_change<F>(&self, operation: F) -> Result<QueueResponse>
+    where
+        F: FnOnce(&dyn MusicKitBackend) -> Result<QueueResponse>,
+    {
+        let before = queue::Queue::from(self.backend.get_queue()?);
+        let response = operation(self.backend.as_ref())?;
+        self.queue_history().record(before);
+        Ok(response)
+    }
+
+    /// Hands a snapshot to the backend, keeping playback going if it was playing.
+    ///
+    /// An empty snapshot clears the queue, since the native player rejects an empty one.
+    fn restore_queue(&self, snapshot: queue::Queue) -> Result<QueueResponse> {
+        if snapshot.is_empty() {
+            return self.backend.clear_queue();
+        }
+        let playing = self.backend.get_playback_state()?.playing;
+        let (tracks, position) = snapshot.into_parts();
+        self.backend.set_queue(tracks, playing, position)
+    }
+}
+
+impl Deref for MusicKit {
+    type Target = dyn MusicKitBackend;
+
+    fn deref(&self) -> &Self::Target {
+        self.backend.as_ref()
+    }
+}
+
 /// An extension trait for Tauri's `Manager` that provides access to the MusicKit plugin API.
 pub trait MusicKitExt<R: Runtime> {
-    fn music_kit(&self) -> tauri::State<'_, MusicKitPlugin<R>>;
+    fn music_kit(&self) -> tauri::State<'_, MusicKit>;
 }
 
 impl<R: Runtime, T: Manager<R>> MusicKitExt<R> for T {
-    fn music_kit(&self) -> tauri::State<'_, MusicKitPlugin<R>> {
-        self.state::<MusicKitPlugin<R>>()
+    fn music_kit(&self) -> tauri::State<'_, MusicKit> {
+        self.state::<MusicKit>()
     }
 }
 
-/// Initializes the plugin.
+type BackendFactory<R> =
+    Box<dyn FnOnce(&AppHandle<R>) -> Result<Arc<dyn MusicKitBackend>> + Send + 'static>;
+
+type EventHandler<R> = Arc<dyn Fn(&AppHandle<R>, &str, serde_json::Value) + Send + Sync + 'static>;
+
+/// The player events [`Builder::on_event`] handlers receive.
+const PLAYER_EVENTS: [&str; 7] = [
+    PLAYER_ADAPTER_EVENTS_INITIALIZED,
+    PLAYER_ADAPTER_EVENTS_AUTHORIZATION_STATUS_CHANGE,
+    PLAYER_ADAPTER_EVENTS_STATE_UPDATE,
+    PLAYER_ADAPTER_EVENTS_QUEUE_UPDATE,
+    PLAYER_ADAPTER_EVENTS_TRACK_CHANGE,
+    PLAYER_ADAPTER_EVENTS_ERROR,
+    PLAYER_ADAPTER_EVENTS_QUEUE_RESTORED,
+];
+
+/// Configures the plugin from Rust.
+///
+/// Options set here take precedence over the `plugins.musickit` section of
+/// `tauri.conf.json`.
+///
+/// ```rust,ignore
+/// tauri::Builder::default()
+///     .plugin(
+///         tauri_plugin_musickit::Builder::new()
+///             .developer_token("eyJhbGciOiJFUzI1NiIs...")
+///             .storefront("gb")
+///             .on_event(|_app, event, payload| println!("{event}: {payload}"))
+///             .build(),
+///     )
+/// # ;
+/// ```
+pub struct Builder<R: Runtime> {
+    developer_token: Option<String>,
+    storefront: Option<String>,
+    backend: Option<BackendFactory<R>>,
+    event_handlers: Vec<EventHandler<R>>,
+}
+
+impl<R: Runtime> Default for Builder<R> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<R: Runtime> Builder<R> {
+    pub fn new() -> Self {
+        Self {
+            developer_token: None,
+            storefront: None,
+            backend: None,
+            event_handlers: Vec::new(),
+        }
+    }
+
+    /// Sets the developer token, replacing any `developerToken` or `developerKey`
+    /// from the config file.
+    pub fn developer_token(mut self, token: impl Into<String>) -> Self {
+        self.developer_token = Some(token.into());
+        self
+    }
+
+    /// Sets the default storefront.
+    pub fn storefront(mut self, storefront: impl Into<String>) -> Self {
+        self.storefront = Some(storefront.into());
+        self
+    }
+
+    /// Drives a custom [`MusicKitBackend`] instead of the native one.
+    ///
+    /// `factory` runs during plugin setup and receives the app handle, so the backend can
+    /// emit events or reach other managed state. The `backend` config option is ignored.
+    pub fn backend<B, F>(mut self, factory: F) -> Self
+    where
+        B: MusicKitBackend,
+        F: FnOnce(&AppHandle<R>) -> Result<B> + Send + 'static,
+    {
+        self.backend = Some(Box::new(move |app: &AppHandle<R>| {
+            factory(app).map(|backend| Arc::new(backend) as Arc<dyn MusicKitBackend>)
+        }));
+        self
+    }
+
+    /// Calls `handler` with the name and payload of every `PLAYER_ADAPTER_EVENTS_*`
+    /// event the backend emits.
+    pub fn on_event<F>(mut self, handler: F) -> Self
+    where
+        F: Fn(&AppHandle<R>, &str, serde_json::Value) + Send + Sync + 'static,
+    {
+        self.event_handlers.push(Arc::new(handler));
+        self
+    }
+
+    pub fn build(self) -> TauriPlugin<R> {
+        let Self {
+            developer_token,
+            storefront,
+            backend,
+            event_handlers,
+        } = self;
+        plugin_builder()
+            .setup(move |app, api| {
+                let mut config = api.config().clone().unwrap_or_default();
+                if developer_token.is_some() {
+                    config.developer_token = developer_token;
+                    config.developer_key = None;
+                }
+                if storefront.is_some() {
+                    config.storefront = storefront;
+                }
+                config.validate()?;
+                if let Some(interval) = config.event_throttle_ms.filter(|&ms| ms > 0) {
+                    // Managed before the backend is created so its emitter picks it up.
+                    app.manage(Arc::new(throttle::StateThrottle::new(
+                        Duration::from_millis(interval),
+                    )));
+                }
+
+                let backend: Arc<dyn MusicKitBackend> = match backend {
+                    Some(factory) => factory(app)?,
+                    #[cfg(feature = "mock")]
+                    None if config.backend == BackendKind::Mock => Arc::new(mock::install(app)),
+                    None => native_backend(app, api)?,
+                };
+                for event in PLAYER_EVENTS {
+                    for handler in &event_handlers {
+                        listen(app, event, handler.clone());
+                    }
+                }
+                setup(app, MusicKit::new(backend, config))?;
+                Ok(())
+            })
+            .build()
+    }
+}
+
+fn native_backend<R: Runtime>(
+    app: &AppHandle<R>,
+    api: tauri::plugin::PluginApi<R, Option<Config>>,
+) -> Result<Arc<dyn MusicKitBackend>> {
+    #[cfg(mobile)]
+    let backend = mobile::init(app, api)?;
+    #[cfg(desktop)]
+    let backend = desktop::init(app, api)?;
+    Ok(Arc::new(backend))
+}
+
+fn listen<R: Runtime>(app: &AppHandle<R>, event: &'static str, handler: EventHandler<R>) {
+    let handle = app.clone();
+    app.listen_any(event, move |emitted| {
+        match serde_json::from_str(emitted.payload()) {
+            Ok(payload) => handler(&handle, event, payload),
+            Err(error) => log::warn!("failed to parse {event} payload: {error}"),
+        }
+    });
+}
+
+/// Initializes the plugin with the backend selected by the `plugins.musickit` config,
+/// the native one for the current platform by default.
 pub fn init<R: Runtime>() -> TauriPlugin<R> {
-    Builder::new("musickit")
-        .invoke_handler(tauri::generate_handler![
-            commands::initialize,
-            commands::authorize,
-            commands::unauthorize,
-            commands::get_authorization_status,
-            commands::get_user_token,
-            commands::set_user_token,
-            commands::get_developer_token,
-            commands::set_developer_token,
-            commands::get_storefront_id,
-            commands::get_storefront,
-            commands::get_queue,
-            commands::play,
-            commands::pause,
-            commands::stop,
-            commands::seek,
-            commands::next,
-            commands::previous,
-            commands::skip_to_item,
-            commands::set_volume,
-            commands::set_queue,
-            commands::update_queue,
-            commands::insert_track_at_position,
-            commands::insert_tracks_at_position,
-            commands::remove_track_from_queue,
-            commands::insert_track_next,
-            commands::insert_track_last,
-            commands::append_tracks_to_queue,
-            commands::get_current_track,
-            commands::get_playback_state,
-        ])
-        .setup(|app, api| {
-            #[cfg(mobile)]
-            let musickit = mobile::init(app, api)?;
-            #[cfg(desktop)]
-            let musickit = desktop::init(app, api)?;
-            app.manage(musickit);
-            Ok(())
-        })
-        .build()
+    Builder::new().build()
+}
+
+/// Initializes the plugin with a custom [`MusicKitBackend`].
+///
+/// Shorthand for `Builder::new().backend(factory).build()`.
+pub fn init_with_backend<R, B, F>(factory: F) -> TauriPlugin<R>
+where
+    R: Runtime,
+    B: MusicKitBackend,
+    F: FnOnce(&AppHandle<R>) -> Result<B> + Send + 'static,
+{
+    Builder::new().backend(factory).build()
+}
+
+/// Initializes the plugin with the in-memory [`MockMusicKit`] backend.
+///
+/// The mock is also managed on its own, so tests can script it through
+/// `app.state::<MockMusicKit<R>>()`.
+#[cfg(feature = "mock")]
+pub fn init_mock<R: Runtime>() -> TauriPlugin<R> {
+    Builder::new().backend(|app| Ok(mock::install(app))).build()
+}
+
+/// Applies the configuration to the backend and registers the plugin state.
+fn setup<R: Runtime>(app: &AppHandle<R>, mut musickit: MusicKit) -> Result<()> {
+    let config = musickit.config().clone();
+    if let Some(token) = config.developer_token {
+        musickit.backend.set_developer_token(token)?;
+    }
+    #[cfg(feature = "jwt")]
+    if let Some(key) = config.developer_key {
+        let mut minter =
+            jwt::DeveloperTokenMinter::from_key_file(key.team_id, key.key_id, key.key_path)?
+                .with_origins(key.origins);
+        if let Some(expiry) = key.expiry_secs {
+            minter = minter.with_expiry(std::time::Duration::from_secs(expiry))?;
+        }
+        musickit.set_developer_token_minter(minter)?;
+    }
+    if let Some(storefront) = config.storefront {
+        musickit.backend.set_storefront(storefront)?;
+    }
+    if config.persist_queue || config.restore_queue {
+        let debounce = config
+            .persist_debounce_ms
+            .map_or(persistence::DEFAULT_PERSIST_DEBOUNCE, Duration::from_millis);
+        let events = emitter::PlayerEmitter::new(app.clone());
+        let persistence = Arc::new(persistence::QueuePersistence::start(
+            &app.path().app_data_dir()?,
+            musickit.backend.clone(),
+            debounce,
+            config.restore_queue,
+            move |event| events.queue_restored(event.clone()),
+        ));
+        if config.persist_queue {
+            let saver = persistence.clone();
+            app.listen_any(PLAYER_ADAPTER_EVENTS_QUEUE_UPDATE, move |_| {
+                saver.schedule_save()
+            });
+            // Save the position once playback pauses or stops, not on every tick.
+            let saver = persistence.clone();
+            app.listen_any(PLAYER_ADAPTER_EVENTS_STATE_UPDATE, move |emitted| {
+                match serde_json::from_str::<StateUpdateEvent>(emitted.payload()) {
+                    Ok(state) => saver.playback_changed(state.playing),
+                    Err(error) => log::warn!("failed to parse the state update: {error}"),
+                }
+            });
+        }
+        musickit.persistence = Some(persistence);
+    }
+    #[cfg(feature = "api")]
+    if let Some(artwork) = &config.artwork_cache {
+        let max_size = artwork
+            .max_size_mb
+            .map_or(artwork_cache::DEFAULT_ARTWORK_CACHE_SIZE, |mb| {
+                mb.saturating_mul(1024 * 1024)
+            });
+        let cache = Arc::new(artwork_cache::ArtworkCache::open(
+            app.path().app_cache_dir()?.join("musickit-artwork"),
+            max_size,
+        )?);
+        let prefetch_count = artwork
+            .prefetch_count
+            .unwrap_or(artwork_cache::DEFAULT_PREFETCH_COUNT);
+        if prefetch_count > 0 {
+            artwork_cache::prefetch_upcoming(
+                app,
+                cache.clone(),
+                prefetch_count,
+                artwork
+                    .prefetch_size
+                    .unwrap_or(artwork_cache::DEFAULT_PREFETCH_SIZE),
+            );
+        }
+        musickit.artwork_cache = Some(cache);
+    }
+    for event in subscription::SUBSCRIBED_EVENTS {
+        let sender = musickit.events.clone();
+        let clock = musickit.clock.clone();
+        app.listen_any(event, move |emitted| {
+            match PlayerEvent::parse(event, emitted.payload()) {
+                Some(Ok(player_event)) => {
+                    if let PlayerEvent::StateUpdate(state) = &player_event {
+                        clock
+                            .lock()
+                            .unwrap_or_else(PoisonError::into_inner)
+                            .update(state);
+                    }
+                    // Sending only fails while nobody is subscribed.
+                    let _ = sender.send(player_event);
+                }
+                Some(Err(error)) => log::warn!("failed to parse {event} payload: {error}"),
+                None => {}
+            }
+        });
+    }
+    app.manage(musickit);
+    if config.auto_initialize {
+        app.music_kit().initialize()?;
+    }
+    Ok(())
+}
+
+fn plugin_builder<R: Runtime>() -> plugin::Builder<R, Option<Config>> {
+    let builder = plugin::Builder::<R, Option<Config>>::new("musickit");
+    #[cfg(feature = "api")]
+    let builder = builder.register_asynchronous_uri_scheme_protocol(
+        artwork_cache::ARTWORK_SCHEME,
+        artwork_cache::protocol,
+    );
+    builder.invoke_handler(tauri::generate_handler![
+        commands::initialize,
+        commands::authorize,
+        commands::unauthorize,
+        commands::get_authorization_status,
+        commands::get_user_token,
+        commands::set_user_token,
+        commands::get_developer_token,
+        commands::set_developer_token,
+        commands::get_storefront_id,
+        commands::get_storefront,
+        commands::set_storefront,
+        commands::get_queue,
+        commands::play,
+        commands::pause,
+        commands::stop,
+        commands::seek,
+        commands::next,
+        commands::previous,
+        commands::skip_to_item,
+        commands::set_volume,
+        commands::set_shuffle_mode,
+        commands::get_shuffle_mode,
+        commands::set_repeat_mode,
+        commands::get_repeat_mode,
+        commands::set_queue,
+        commands::update_queue,
+        commands::insert_track_at_position,
+        commands::insert_tracks_at_position,
+        commands::remove_track_from_queue,
+        commands::insert_track_next,
+        commands::insert_track_last,
+        commands::append_tracks_to_queue,
+        commands::get_current_track,
+        commands::get_playback_state,
+        commands::remove_track_at_position,
+        commands::remove_range,
+        commands::clear_queue,
+        commands::move_queue_item,
+        commands::move_queue_items,
+        commands::undo_queue_change,
+        commands::redo_queue_change,
+        #[cfg(feature = "api")]
+        commands::search_catalog,
+        #[cfg(feature = "api")]
+        commands::get_search_suggestions,
+        #[cfg(feature = "api")]
+        commands::prefetch_artwork,
+        #[cfg(feature = "api")]
+        commands::evict_artwork,
+        #[cfg(feature = "api")]
+        commands::clear_artwork_cache,
+        #[cfg(feature = "api")]
+        commands::get_album,
+        #[cfg(feature = "api")]
+        commands::get_artist,
+        #[cfg(feature = "api")]
+        commands::get_playlist,
+        #[cfg(feature = "api")]
+        commands::set_queue_from_container,
+    ])
 }
 
+#[cfg(all(test, feature = "mock"))]
+mod tests {
+    #[cfg(feature = "api")]
+    use serde_json::{json, Value};
+    use tauri::{
+        test::{mock_app, MockRuntime},
+        App,
+    };
 
+    use super::*;
+    #[cfg(feature = "api")]
+    use crate::api::stand_in::StandIn;
+
+    fn musickit() -> (App<MockRuntime>, MockMusicKit<MockRuntime>, MusicKit) {
+        let app = mock_app();
+        let mock = MockMusicKit::new(app.handle().clone());
+        let musickit = MusicKit::new(Arc::new(mock.clone()), Config::default());
+        (app, mock, musickit)
+    }
+
+    fn track(id: &str) -> MusicKitTrack {
+        MusicKitTrack {
+            id: id.to_string(),
+            ..Default::default()
+        }
+    }
+
+    fn ids(queue: &QueueResponse) -> Vec<&str> {
+        queue.items.iter().map(|track| track.id.as_str()).collect()
+    }
+
+    #[test]
+    fn undo_and_redo_restore_the_queue_on_the_backend() {
+        let (_app, mock, musickit) = musickit();
+        musickit
+            .set_queue(vec![track("a"), track("b"), track("c")], false, 1)
+            .unwrap();
+        musickit.remove_track_at_position(0).unwrap();
+
+        let queue = musickit.undo_queue_change().unwrap();
+        assert_eq!(ids(&queue), ["a", "b", "c"]);
+        assert_eq!(queue.position, 1);
+        assert_eq!(ids(&mock.get_queue().unwrap()), ["a", "b", "c"]);
+
+        let queue = musickit.redo_queue_change().unwrap();
+        assert_eq!(ids(&queue), ["b", "c"]);
+        assert_eq!(queue.position, 0);
+        assert_eq!(ids(&mock.get_queue().unwrap()), ["b", "c"]);
+        assert!(matches!(
+            musickit.redo_queue_change(),
+            Err(Error::NothingToRedo)
+        ));
+    }
+
+    #[test]
+    fn undoing_back_to_an_empty_queue_clears_it() {
+        let (_app, mock, musickit) = musickit();
+        musickit
+            .set_queue(vec![track("a"), track("b")], true, 0)
+            .unwrap();
+
+        let queue = musickit.undo_queue_change().unwrap();
+        assert!(queue.items.is_empty());
+        assert!(mock.get_queue().unwrap().items.is_empty());
+        assert!(!mock.get_playback_state().unwrap().playing);
+        assert!(matches!(
+            musickit.undo_queue_change(),
+            Err(Error::NothingToUndo)
+        ));
+
+        let queue = musickit.redo_queue_change().unwrap();
+        assert_eq!(ids(&queue), ["a", "b"]);
+    }
+
+    #[cfg(feature = "api")]
+    fn ok(body: Value) -> (u16, String) {
+        (200, body.to_string())
+    }
+
+    #[cfg(feature = "api")]
+    fn song(id: &str) -> Value {
+        json!({
+            "id": id,
+            "type": "songs",
+            "attributes": {
+                "name": id,
+                "artistName": "Artist",
+                "playParams": { "id": id, "kind": "song" },
+            },
+        })
+    }
+
+    /// An album or playlist whose tracks span two pages.
+    #[cfg(feature = "api")]
+    fn paged_container(kind: &str, id: &str) -> Vec<(u16, String)> {
+        vec![
+            ok(json!({ "data": [{
+                "id": id,
+                "type": kind,
+                "attributes": { "name": id, "artistName": "Artist" },
+                "relationships": { "tracks": {
+                    "data": [song("s1"), song("s2")],
+                    "next": format!("/v1/catalog/us/{kind}/{id}/tracks?offset=2"),
+                } },
+            }] })),
+            ok(json!({ "data": [song("s3")] })),
+        ]
+    }
+
+    #[cfg(feature = "api")]
+    #[test]
+    fn albums_and_playlists_are_queued_with_every_page_of_tracks() {
+        let (_app, mock, musickit) = musickit();
+        tauri::async_runtime::block_on(async {
+            for (kind, path, id) in [
+                (ContainerKind::Album, "albums", "1"),
+                (ContainerKind::Playlist, "playlists", "pl.1"),
+            ] {
+                let mut server = StandIn::start(paged_container(path, id)).await;
+                let client = server.client("token");
+
+                let queue = musickit
+                    .queue_container(&client, kind, id.to_string(), 2, true)
+                    .await
+                    .unwrap();
+                assert_eq!(
+                    server.request_line().await,
+                    format!("GET /v1/catalog/us/{path}/{id}?include=tracks HTTP/1.1")
+                );
+                assert_eq!(
+                    server.request_line().await,
+                    format!("GET /v1/catalog/us/{path}/{id}/tracks?offset=2 HTTP/1.1")
+                );
+                assert_eq!(ids(&queue), ["s1", "s2", "s3"]);
+                assert_eq!(queue.position, 2);
+                assert!(mock.get_playback_state().unwrap().playing);
+                assert_eq!(mock.get_shuffle_mode().unwrap(), ShuffleMode::Songs);
+            }
+        });
+    }
+
+    #[cfg(feature = "api")]
+    #[test]
+    fn artists_are_queued_with_their_top_songs() {
+        let (_app, _mock, musickit) = musickit();
+        tauri::async_runtime::block_on(async {
+            let mut server =
+                StandIn::start(vec![ok(json!({ "data": [song("s1"), song("s2")] }))]).await;
+            let client = server.client("token");
+
+            let queue = musickit
+                .queue_container(&client, ContainerKind::Artist, "7".to_string(), 0, false)
+                .await
+                .unwrap();
+            assert_eq!(
+                server.request_line().await,
+                "GET /v1/catalog/us/artists/7/view/top-songs HTTP/1.1"
+            );
+            assert_eq!(ids(&queue), ["s1", "s2"]);
+            assert_eq!(queue.position, 0);
+        });
+    }
+
+    #[cfg(feature = "api")]
+    #[test]
+    fn a_start_index_past_the_tracks_is_out_of_range() {
+        let (_app, mock, musickit) = musickit();
+        tauri::async_runtime::block_on(async {
+            let server = StandIn::start(paged_container("albums", "1")).await;
+            let client = server.client("token");
+
+            let error = musickit
+                .queue_container(&client, ContainerKind::Album, "1".to_string(), 3, false)
+                .await
+                .unwrap_err();
+            assert!(
+                matches!(
+                    error,
+                    Error::QueuePositionOutOfRange {
+                        position: 3,
+                        len: 3
+                    }
+                ),
+                "{error:?}"
+            );
+            assert!(mock.get_queue().unwrap().items.is_empty());
+            assert!(matches!(
+                musickit.undo_queue_change(),
+                Err(Error::NothingToUndo)
+            ));
+        });
+    }
+
+    #[cfg(feature = "api")]
+    #[test]
+    fn stations_are_handed_over_without_resolving_tracks() {
+        let (_app, _mock, musickit) = musickit();
+        tauri::async_runtime::block_on(async {
+            // No responses are served, so any API request would fail the test.
+            let server = StandIn::start(vec![]).await;
+            let client = server.client("token");
+
+            // The mock backend cannot play stations, which shows the index was not checked.
+            let error = musickit
+                .queue_container(
+                    &client,
+                    ContainerKind::Station,
+                    "ra.1".to_string(),
+                    99,
+                    false,
+                )
+                .await
+                .unwrap_err();
+            assert!(matches!(error, Error::PlatformNotSupported), "{error:?}");
+        });
+    }
+
+    #[cfg(feature = "api")]
+    #[test]
+    fn queuing_a_container_can_be_undone() {
+        let (_app, _mock, musickit) = musickit();
+        tauri::async_runtime::block_on(async {
+            let server = StandIn::start(paged_container("albums", "1")).await;
+            let client = server.client("token");
+            musickit.set_queue(vec![track("x")], false, 0).unwrap();
+
+            musickit
+                .queue_container(&client, ContainerKind::Album, "1".to_string(), 0, false)
+                .await
+                .unwrap();
+            let queue = musickit.undo_queue_change().unwrap();
+            assert_eq!(ids(&queue), ["x"]);
+            let queue = musickit.redo_queue_change().unwrap();
+            assert_eq!(ids(&queue), ["s1", "s2", "s3"]);
+        });
+    }
+}
@@ -1,14 +1,18 @@
 //! The mobile-specific implementation for the plugin.
 
 use crate::{
+    emitter::{native, PlayerEmitter},
     models::{
-        AuthorizationResponse, AuthorizationStatusResponse, GetUserTokenResponse, MusicKitTrack,
-        QueueOperationResponse, QueueResponse, StateUpdateEvent, UnauthorizeResponse,
+        AuthorizationResponse, AuthorizationStatus, AuthorizationStatusResponse, ErrorEvent,
+        GetUserTokenResponse, MusicKitTrack, QueueContainer, QueueOperationResponse, QueueResponse,
+        RepeatMode, ShuffleMode, StateUpdateEvent, UnauthorizeResponse,
     },
-    Result,
+    queue::{Queue, QueueSync},
+    MusicKitBackend, Result,
 };
-use serde::{de::DeserializeOwned, Serialize};
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
 use tauri::{
+    ipc::Channel,
     plugin::{PluginApi, PluginHandle},
     AppHandle, Runtime,
 };
@@ -24,14 +28,16 @@ unsafe fn init_plugin_wrapper() -> *const std::ffi::c_void {
 }
 
 pub fn init<R: Runtime, C: DeserializeOwned>(
-    _app: &AppHandle<R>,
+    app: &AppHandle<R>,
     api: PluginApi<R, C>,
 ) -> crate::Result<MusicKitPlugin<R>> {
     #[cfg(target_os = "ios")]
     let handle = api.register_ios_plugin(init_plugin_wrapper)?;
     #[cfg(target_os = "android")]
     let handle = api.register_android_plugin("app.tauri.musickit", "MusicKitPlugin")?;
-    Ok(MusicKitPlugin::new(handle))
+    let plugin = MusicKitPlugin::new(handle, app.clone());
+    plugin.listen_native()?;
+    Ok(plugin)
 }
 
 #[derive(Serialize)]
@@ -48,219 +54,354 @@ struct SetQueuePayload {
     start_position: usize,
 }
 
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct RearrangeQueuePayload {
+    tracks: Vec<MusicKitTrack>,
+    position: usize,
+}
+
 #[derive(Serialize)]
 #[serde(rename_all = "camelCase")]
 struct SetDeveloperTokenPayload {
     token: String,
 }
 
+#[derive(Serialize, Deserialize)]
+struct ModePayload<T> {
+    mode: T,
+}
+
+#[derive(Serialize)]
+struct RegisterListenerPayload {
+    event: &'static str,
+    handler: Channel,
+}
+
 #[derive(Debug)]
-pub struct MusicKitPlugin<R: Runtime>(PluginHandle<R>);
+pub struct MusicKitPlugin<R: Runtime>(PluginHandle<R>, PlayerEmitter<R>);
+
+impl<R: Runtime> Clone for MusicKitPlugin<R> {
+    fn clone(&self) -> Self {
+        Self(self.0.clone(), self.1.clone())
+    }
+}
 
 impl<R: Runtime> MusicKitPlugin<R> {
-    pub fn new(handle: PluginHandle<R>) -> Self {
-        Self(handle)
+    pub fn new(handle: PluginHandle<R>, app: AppHandle<R>) -> Self {
+        Self(handle, PlayerEmitter::new(app))
     }
 
-    pub fn initialize(&self) -> Result<()> {
-        self.0
-            .run_mobile_plugin("initialize", ())
-            .map_err(Into::into)
+    /// Subscribes to the native triggers and re-emits each of them as the matching
+    /// `PLAYER_ADAPTER_EVENTS_*` event.
+    fn listen_native(&self) -> Result<()> {
+        for event in native::ALL {
+            let plugin = self.clone();
+            let handler = Channel::new(move |_| {
+                // The state is read back through the plugin off the native thread.
+                let plugin = plugin.clone();
+                tauri::async_runtime::spawn_blocking(move || {
+                    if let Err(error) = plugin.1.forward_native(event, &plugin) {
+                        plugin.1.error(ErrorEvent {
+                            error: error.to_string(),
+                            code: Some(error.code().as_str().to_string()),
+                        });
+                    }
+                });
+                Ok(())
+            });
+            self.0.run_mobile_plugin::<()>(
+                "registerListener",
+                RegisterListenerPayload { event, handler },
+            )?;
+        }
+        Ok(())
     }
 
-    pub fn authorize(&self) -> Result<AuthorizationResponse> {
-        self.0
-            .run_mobile_plugin("authorize", ())
-            .map_err(Into::into)
+    /// Resolves a queue operation against the native queue and hands the result back to
+    /// the native player. When the current item survives, the items around it are
+    /// rearranged in place so playback carries on; otherwise the queue is replaced, or
+    /// cleared once nothing is left.
+    fn apply_queue<F>(&self, operation: F) -> Result<QueueResponse>
+    where
+        F: FnOnce(&mut Queue) -> Result<()>,
+    {
+        let before = Queue::from(self.get_queue()?);
+        let mut after = before.clone();
+        operation(&mut after)?;
+        let state = self.get_playback_state()?;
+        match QueueSync::plan(&before, after) {
+            QueueSync::Clear => self.clear_queue(),
+            // Without a now playing item there is nothing to keep playing.
+            QueueSync::Rearrange { tracks, position } if state.current_track.is_some() => {
+                self.0
+                    .run_mobile_plugin::<QueueOperationResponse>(
+                        "rearrangeQueue",
+                        RearrangeQueuePayload { tracks, position },
+                    )?
+                    .into_result()?;
+                self.get_queue()
+            }
+            QueueSync::Rearrange { tracks, position } | QueueSync::Replace { tracks, position } => {
+                self.set_queue(tracks, state.playing, position)
+            }
+        }
+    }
+}
+
+impl<R: Runtime> MusicKitBackend for MusicKitPlugin<R> {
+    fn initialize(&self) -> Result<()> {
+        self.0.run_mobile_plugin::<()>("initialize", ())?;
+        self.1.initialized();
+        Ok(())
+    }
+
+    fn authorize(&self) -> Result<AuthorizationResponse> {
+        let response: AuthorizationResponse = self.0.run_mobile_plugin("authorize", ())?;
+        if response.error.is_none() {
+            self.1.authorization_status_change(response.status.clone());
+        }
+        Ok(response)
     }
 
-    pub fn unauthorize(&self) -> Result<UnauthorizeResponse> {
+    fn unauthorize(&self) -> Result<UnauthorizeResponse> {
+        self.1
+            .authorization_status_change(AuthorizationStatus::NotAuthorized);
         Ok(UnauthorizeResponse {
             status: "unauthorized".to_string(),
             error: None,
         })
     }
 
-    pub fn get_authorization_status(&self) -> Result<AuthorizationStatusResponse> {
+    fn get_authorization_status(&self) -> Result<AuthorizationStatusResponse> {
         self.0
             .run_mobile_plugin("getAuthorizationStatus", serde_json::json!({}))
             .map_err(Into::into)
     }
 
-    pub fn get_user_token(&self) -> Result<GetUserTokenResponse> {
+    fn get_user_token(&self) -> Result<GetUserTokenResponse> {
         self.0
             .run_mobile_plugin("getUserToken", serde_json::json!({}))
             .map_err(Into::into)
     }
 
-    pub fn get_developer_token(&self) -> Result<Option<String>> {
+    fn get_developer_token(&self) -> Result<Option<String>> {
         self.0
             .run_mobile_plugin("getDeveloperToken", serde_json::json!({}))
             .map_err(Into::into)
     }
 
-    pub fn set_developer_token(&self, token: String) -> Result<()> {
+    fn set_developer_token(&self, token: String) -> Result<()> {
         self.0
             .run_mobile_plugin("setDeveloperToken", SetDeveloperTokenPayload { token })
             .map_err(Into::into)
     }
 
-    pub fn set_user_token(&self, token: String) -> Result<()> {
+    fn set_user_token(&self, token: String) -> Result<()> {
         self.0
             .run_mobile_plugin("setUserToken", token)
             .map_err(Into::into)
     }
 
-    pub fn get_storefront_id(&self) -> Result<Option<String>> {
+    fn get_storefront_id(&self) -> Result<Option<String>> {
         self.0
             .run_mobile_plugin("getStorefrontId", serde_json::json!({}))
             .map_err(Into::into)
     }
 
-    pub fn get_storefront(&self) -> Result<Option<serde_json::Value>> {
+    fn get_storefront(&self) -> Result<Option<serde_json::Value>> {
         self.0
             .run_mobile_plugin("getStorefront", serde_json::json!({}))
             .map_err(Into::into)
     }
 
-    pub fn set_storefront(&self, storefront: String) -> Result<()> {
+    fn set_storefront(&self, storefront: String) -> Result<()> {
         self.0
             .run_mobile_plugin("setStorefront", storefront)
             .map_err(Into::into)
     }
 
-    pub fn get_queue(&self) -> Result<QueueResponse> {
+    fn get_queue(&self) -> Result<QueueResponse> {
         self.0
             .run_mobile_plugin("getQueue", serde_json::json!({}))
             .map_err(Into::into)
     }
 
-    pub fn play(&self) -> Result<()> {
+    fn play(&self) -> Result<()> {
         self.0
             .run_mobile_plugin("play", serde_json::json!({}))
             .map_err(Into::into)
     }
 
-    pub fn pause(&self) -> Result<()> {
+    fn pause(&self) -> Result<()> {
         self.0
             .run_mobile_plugin("pause", serde_json::json!({}))
             .map_err(Into::into)
     }
 
-    pub fn stop(&self) -> Result<()> {
+    fn stop(&self) -> Result<()> {
         self.0
             .run_mobile_plugin("stop", serde_json::json!({}))
             .map_err(Into::into)
     }
 
-    pub fn seek(&self, time: f64) -> Result<()> {
+    fn seek(&self, time: f64) -> Result<()> {
         self.0
             .run_mobile_plugin("seek", SeekPayload { time })
             .map_err(Into::into)
     }
 
-    pub fn next(&self) -> Result<()> {
+    fn next(&self) -> Result<()> {
         self.0
             .run_mobile_plugin("next", serde_json::json!({}))
             .map_err(Into::into)
     }
 
-    pub fn previous(&self) -> Result<()> {
+    fn previous(&self) -> Result<()> {
         self.0
             .run_mobile_plugin("previous", serde_json::json!({}))
             .map_err(Into::into)
     }
 
-    pub fn skip_to_item(&self, track_id: String, start_playing: bool) -> Result<()> {
+    fn skip_to_item(&self, track_id: String, start_playing: bool) -> Result<()> {
         self.0
             .run_mobile_plugin("skipToItem", (track_id, start_playing))
             .map_err(Into::into)
     }
 
-    pub fn set_volume(&self, volume: f64) -> Result<()> {
+    fn set_volume(&self, volume: f64) -> Result<()> {
         self.0
             .run_mobile_plugin("setVolume", volume)
             .map_err(Into::into)
     }
 
-    pub fn set_queue(
+    fn set_shuffle_mode(&self, mode: ShuffleMode) -> Result<()> {
+        self.0
+            .run_mobile_plugin("setShuffleMode", ModePayload { mode })
+            .map_err(Into::into)
+    }
+
+    fn get_shuffle_mode(&self) -> Result<ShuffleMode> {
+        self.0
+            .run_mobile_plugin("getShuffleMode", serde_json::json!({}))
+            .map(|payload: ModePayload<ShuffleMode>| payload.mode)
+            .map_err(Into::into)
+    }
+
+    fn set_repeat_mode(&self, mode: RepeatMode) -> Result<()> {
+        self.0
+            .run_mobile_plugin("setRepeatMode", ModePayload { mode })
+            .map_err(Into::into)
+    }
+
+    fn get_repeat_mode(&self) -> Result<RepeatMode> {
+        self.0
+            .run_mobile_plugin("getRepeatMode", serde_json::json!({}))
+            .map(|payload: ModePayload<RepeatMode>| payload.mode)
+            .map_err(Into::into)
+    }
+
+    fn set_queue(
         &self,
         tracks: Vec<MusicKitTrack>,
         start_playing: bool,
         start_position: usize,
-    ) -> Result<QueueOperationResponse> {
+    ) -> Result<QueueResponse> {
         self.0
-            .run_mobile_plugin(
+            .run_mobile_plugin::<QueueOperationResponse>(
                 "setQueue",
                 SetQueuePayload {
                     tracks,
                     start_playing,
                     start_position,
                 },
-            )
-            .map_err(Into::into)
+            )?
+            .into_result()?;
+        self.get_queue()
     }
 
-    pub fn update_queue(&self, tracks: Vec<MusicKitTrack>) -> Result<QueueOperationResponse> {
+    fn set_queue_from_container(&self, container: QueueContainer) -> Result<QueueResponse> {
         self.0
-            .run_mobile_plugin("updateQueue", tracks)
-            .map_err(Into::into)
+            .run_mobile_plugin::<QueueOperationResponse>("setQueueFromContainer", container)?
+            .into_result()?;
+        self.get_queue()
+    }
+
+    fn update_queue(&self, tracks: Vec<MusicKitTrack>) -> Result<QueueResponse> {
+        self.apply_queue(|queue| {
+            queue.update(tracks);
+            Ok(())
+        })
     }
 
-    pub fn insert_track_at_position(
+    fn insert_track_at_position(
         &self,
         track: MusicKitTrack,
         position: usize,
-    ) -> Result<QueueOperationResponse> {
-        self.0
-            .run_mobile_plugin("insertTrackAtPosition", (track, position))
-            .map_err(Into::into)
+    ) -> Result<QueueResponse> {
+        self.apply_queue(|queue| queue.insert(position, vec![track]))
     }
 
-    pub fn insert_tracks_at_position(
+    fn insert_tracks_at_position(
         &self,
         tracks: Vec<MusicKitTrack>,
         position: usize,
-    ) -> Result<QueueOperationResponse> {
-        self.0
-            .run_mobile_plugin("insertTracksAtPosition", (tracks, position))
-            .map_err(Into::into)
+    ) -> Result<QueueResponse> {
+        self.apply_queue(|queue| queue.insert(position, tracks))
     }
 
-    pub fn remove_track_from_queue(&self, track_id: String) -> Result<QueueOperationResponse> {
-        self.0
-            .run_mobile_plugin("removeTrackFromQueue", track_id)
-            .map_err(Into::into)
+    fn remove_track_from_queue(&self, track_id: String) -> Result<QueueResponse> {
+        self.apply_queue(|queue| queue.remove_track(&track_id).map(drop))
     }
 
-    pub fn insert_track_next(&self, track: MusicKitTrack) -> Result<QueueOperationResponse> {
-        self.0
-            .run_mobile_plugin("insertTrackNext", track)
-            .map_err(Into::into)
+    fn remove_track_at_position(&self, position: usize) -> Result<QueueResponse> {
+        self.apply_queue(|queue| queue.remove_at(position).map(drop))
     }
 
-    pub fn insert_track_last(&self, track: MusicKitTrack) -> Result<QueueOperationResponse> {
-        self.0
-            .run_mobile_plugin("insertTrackLast", track)
-            .map_err(Into::into)
+    fn remove_range(&self, start: usize, end: usize) -> Result<QueueResponse> {
+        self.apply_queue(|queue| queue.remove_range(start, end).map(drop))
     }
 
-    pub fn append_tracks_to_queue(
-        &self,
-        tracks: Vec<MusicKitTrack>,
-    ) -> Result<QueueOperationResponse> {
+    fn clear_queue(&self) -> Result<QueueResponse> {
         self.0
-            .run_mobile_plugin("appendTracksToQueue", tracks)
-            .map_err(Into::into)
+            .run_mobile_plugin::<QueueOperationResponse>("clearQueue", serde_json::json!({}))?
+            .into_result()?;
+        self.get_queue()
+    }
+
+    fn insert_track_next(&self, track: MusicKitTrack) -> Result<QueueResponse> {
+        self.apply_queue(|queue| {
+            queue.insert_next(vec![track]);
+            Ok(())
+        })
+    }
+
+    fn insert_track_last(&self, track: MusicKitTrack) -> Result<QueueResponse> {
+        self.append_tracks_to_queue(vec![track])
+    }
+
+    fn append_tracks_to_queue(&self, tracks: Vec<MusicKitTrack>) -> Result<QueueResponse> {
+        self.apply_queue(|queue| {
+            queue.append(tracks);
+            Ok(())
+        })
+    }
+
+    fn move_queue_item(&self, from: usize, to: usize) -> Result<QueueResponse> {
+        self.apply_queue(|queue| queue.move_item(from, to))
+    }
+
+    fn move_queue_items(&self, indices: Vec<usize>, to: usize) -> Result<QueueResponse> {
+        self.apply_queue(|queue| queue.move_items(&indices, to))
     }
 
-    pub fn get_current_track(&self) -> Result<Option<MusicKitTrack>> {
+    fn get_current_track(&self) -> Result<Option<MusicKitTrack>> {
         self.0
             .run_mobile_plugin("getCurrentTrack", serde_json::json!({}))
             .map_err(Into::into)
     }
 
-    pub fn get_playback_state(&self) -> Result<StateUpdateEvent> {
+    fn get_playback_state(&self) -> Result<StateUpdateEvent> {
         self.0
             .run_mobile_plugin("getPlaybackState", serde_json::json!({}))
             .map_err(Into::into)
@@ -0,0 +1,643 @@
+//! An in-memory backend for exercising MusicKit flows in app tests.
+//!
+//! [`MockMusicKit`] keeps a real queue, playback position, volume and authorization
+//! state, and emits the same `PLAYER_ADAPTER_EVENTS_*` events as the native backends.
+//! Register it with [`crate::init_mock`] and script it through the managed
+//! `MockMusicKit<R>` state.
+
+use std::sync::{Arc, Mutex, MutexGuard, PoisonError};
+
+use tauri::{AppHandle, Manager, Runtime};
+
+use crate::{emitter::PlayerEmitter, models::*, queue::Queue, Error, MusicKitBackend, Result};
+
+/// Mock MusicKit backend holding all player state in memory.
+#[derive(Debug)]
+pub struct MockMusicKit<R: Runtime> {
+    events: PlayerEmitter<R>,
+    state: Arc<Mutex<MockState>>,
+}
+
+impl<R: Runtime> Clone for MockMusicKit<R> {
+    fn clone(&self) -> Self {
+        Self {
+            events: self.events.clone(),
+            state: self.state.clone(),
+        }
+    }
+}
+
+#[derive(Debug)]
+struct MockState {
+    initialized: bool,
+    authorization_status: AuthorizationStatus,
+    granted_status: AuthorizationStatus,
+    authorization_error: Option<String>,
+    user_token: Option<String>,
+    developer_token: Option<String>,
+    storefront_id: Option<String>,
+    queue: Queue,
+    playing: bool,
+    paused: bool,
+    current_time: f64,
+    volume: f64,
+    shuffle_mode: ShuffleMode,
+    repeat_mode: RepeatMode,
+}
+
+impl Default for MockState {
+    fn default() -> Self {
+        Self {
+            initialized: false,
+            authorization_status: AuthorizationStatus::NotDetermined,
+            granted_status: AuthorizationStatus::Authorized,
+            authorization_error: None,
+            user_token: None,
+            developer_token: None,
+            storefront_id: Some("us".to_string()),
+            queue: Queue::new(),
+            playing: false,
+            paused: false,
+            current_time: 0.0,
+            volume: 1.0,
+            shuffle_mode: ShuffleMode::Off,
+            repeat_mode: RepeatMode::None,
+        }
+    }
+}
+
+impl MockState {
+    fn current_track(&self) -> Option<&MusicKitTrack> {
+        self.queue.current()
+    }
+
+    fn playback_state(&self) -> StateUpdateEvent {
+        let duration = self.current_track().map_or(0.0, |track| track.duration);
+        StateUpdateEvent {
+            playing: self.playing,
+            paused: self.paused,
+            current_track: self.current_track().cloned(),
+            current_time: self.current_time,
+            duration,
+            progress: if duration > 0.0 {
+                self.current_time / duration
+            } else {
+                0.0
+            },
+            queue_position: self.queue.position(),
+            shuffle_mode: self.shuffle_mode.clone(),
+            repeat_mode: self.repeat_mode.clone(),
+            volume: self.volume,
+        }
+    }
+
+    /// Moves the cursor to `position`, rewinding the playback clock.
+    fn jump_to(&mut self, position: usize) -> Result<()> {
+        self.queue.jump_to(position)?;
+        self.current_time = 0.0;
+        Ok(())
+    }
+
+    /// Applies a queue operation, rewinding the playback clock when the current item
+    /// changes.
+    fn edit_queue<T>(&mut self, operation: impl FnOnce(&mut Queue) -> Result<T>) -> Result<T> {
+        let current_id = self.current_track().map(|track| track.id.clone());
+        let output = operation(&mut self.queue)?;
+        if self.current_track().map(|track| &track.id) != current_id.as_ref() {
+            self.current_time = 0.0;
+        }
+        Ok(output)
+    }
+}
+
+/// Creates a mock for `app` and manages a handle to it for scripting.
+pub(crate) fn install<R: Runtime>(app: &AppHandle<R>) -> MockMusicKit<R> {
+    let mock = MockMusicKit::new(app.clone());
+    app.manage(mock.clone());
+    mock
+}
+
+impl<R: Runtime> MockMusicKit<R> {
+    pub fn new(app: AppHandle<R>) -> Self {
+        Self {
+            events: PlayerEmitter::new(app),
+            state: Default::default(),
+        }
+    }
+
+    fn state(&self) -> MutexGuard<'_, MockState> {
+        self.state.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    /// Emits a state update and, when `track_changed` is set, a track change for the
+    /// current item.
+    fn emit_playback(&self, track_changed: bool) {
+        let (state, track) = {
+            let state = self.state();
+            (state.playback_state(), state.current_track().cloned())
+        };
+        if track_changed {
+            if let Some(track) = track {
+                self.events.track_change(track);
+            }
+        }
+        self.events.state_update(state);
+    }
+
+    fn emit_queue(&self) {
+        let event = self.state().queue.to_event();
+        self.events.queue_update(event);
+    }
+
+    /// Applies a queue mutation and emits `QUEUE_UPDATE` when it succeeds.
+    fn mutate_queue<F>(&self, mutation: F) -> Result<QueueResponse>
+    where
+        F: FnOnce(&mut MockState) -> Result<()>,
+    {
+        let response = {
+            let mut state = self.state();
+            mutation(&mut state)?;
+            state.queue.to_response()
+        };
+        self.emit_queue();
+        Ok(response)
+    }
+
+    /// Sets the status the next successful [`MusicKitBackend::authorize`] call grants.
+    pub fn set_authorization_result(&self, status: impl Into<AuthorizationStatus>) {
+        let mut state = self.state();
+        state.granted_status = status.into();
+        state.authorization_error = None;
+    }
+
+    /// Makes every following [`MusicKitBackend::authorize`] call fail with `error`.
+    pub fn fail_authorization(&self, error: impl Into<String>) {
+        self.state().authorization_error = Some(error.into());
+    }
+
+    /// Emits `event` as a `PLAYER_ADAPTER_EVENTS.ERROR`, as a native player would.
+    pub fn inject_error(&self, event: ErrorEvent) {
+        self.events.error(event);
+    }
+
+    /// Advances the playback clock by `seconds` while playing, moving on to the next
+    /// queue item when the current one ends, as the repeat mode dictates.
+    pub fn advance(&self, seconds: f64) {
+        let track_changed = {
+            let mut state = self.state();
+            if !state.playing {
+                return;
+            }
+            let duration = state.current_track().map_or(0.0, |track| track.duration);
+            let next = state.queue.position() + 1;
+            state.current_time += seconds;
+            if state.current_time < duration {
+                false
+            } else if state.repeat_mode == RepeatMode::One {
+                state.current_time = 0.0;
+                false
+            } else if state.jump_to(next).is_ok() {
+                true
+            } else if state.repeat_mode == RepeatMode::All {
+                state.jump_to(0).is_ok()
+            } else {
+                state.current_time = duration;
+                state.playing = false;
+                false
+            }
+        };
+        self.emit_playback(track_changed);
+    }
+}
+
+impl<R: Runtime> MusicKitBackend for MockMusicKit<R> {
+    fn initialize(&self) -> Result<()> {
+        let first_time = !std::mem::replace(&mut self.state().initialized, true);
+        if first_time {
+            self.events.initialized();
+        }
+        Ok(())
+    }
+
+    fn authorize(&self) -> Result<AuthorizationResponse> {
+        let response = {
+            let mut state = self.state();
+            match state.authorization_error.clone() {
+                Some(error) => AuthorizationResponse {
+                    status: AuthorizationStatus::Error,
+                    error: Some(error),
+                },
+                None => {
+                    state.authorization_status = state.granted_status.clone();
+                    if state.authorization_status.is_authorized() && state.user_token.is_none() {
+                        state.user_token = Some("mock-user-token".to_string());
+                    }
+                    AuthorizationResponse {
+                        status: state.authorization_status.clone(),
+                        error: None,
+                    }
+                }
+            }
+        };
+        if response.error.is_none() {
+            self.events
+                .authorization_status_change(response.status.clone());
+        }
+        Ok(response)
+    }
+
+    fn unauthorize(&self) -> Result<UnauthorizeResponse> {
+        {
+            let mut state = self.state();
+            state.authorization_status = AuthorizationStatus::NotAuthorized;
+            state.user_token = None;
+        }
+        self.events
+            .authorization_status_change(AuthorizationStatus::NotAuthorized);
+        Ok(UnauthorizeResponse {
+            status: "unauthorized".to_string(),
+            error: None,
+        })
+    }
+
+    fn get_authorization_status(&self) -> Result<AuthorizationStatusResponse> {
+        Ok(AuthorizationStatusResponse {
+            status: self.state().authorization_status.clone(),
+        })
+    }
+
+    fn get_user_token(&self) -> Result<GetUserTokenResponse> {
+        Ok(GetUserTokenResponse {
+            token: self.state().user_token.clone(),
+        })
+    }
+
+    fn get_developer_token(&self) -> Result<Option<String>> {
+        Ok(self.state().developer_token.clone())
+    }
+
+    fn set_developer_token(&self, token: String) -> Result<()> {
+        self.state().developer_token = Some(token);
+        Ok(())
+    }
+
+    fn set_user_token(&self, token: String) -> Result<()> {
+        self.state().user_token = Some(token);
+        Ok(())
+    }
+
+    fn get_storefront_id(&self) -> Result<Option<String>> {
+        Ok(self.state().storefront_id.clone())
+    }
+
+    fn get_storefront(&self) -> Result<Option<serde_json::Value>> {
+        Ok(self
+            .state()
+            .storefront_id
+            .clone()
+            .map(|id| serde_json::json!({ "id": id, "type": "storefronts" })))
+    }
+
+    fn set_storefront(&self, storefront: String) -> Result<()> {
+        self.state().storefront_id = Some(storefront);
+        Ok(())
+    }
+
+    fn get_queue(&self) -> Result<QueueResponse> {
+        Ok(self.state().queue.to_response())
+    }
+
+    fn play(&self) -> Result<()> {
+        {
+            let mut state = self.state();
+            if state.queue.is_empty() {
+                return Err(Error::MusicKitError("Queue is empty".to_string()));
+            }
+            state.playing = true;
+            state.paused = false;
+        }
+        self.emit_playback(false);
+        Ok(())
+    }
+
+    fn pause(&self) -> Result<()> {
+        {
+            let mut state = self.state();
+            state.paused = state.playing || state.paused;
+            state.playing = false;
+        }
+        self.emit_playback(false);
+        Ok(())
+    }
+
+    fn stop(&self) -> Result<()> {
+        {
+            let mut state = self.state();
+            state.playing = false;
+            state.paused = false;
+            state.current_time = 0.0;
+        }
+        self.emit_playback(false);
+        Ok(())
+    }
+
+    fn seek(&self, time: f64) -> Result<()> {
+        {
+            let mut state = self.state();
+            let duration = state.current_track().map_or(0.0, |track| track.duration);
+            state.current_time = time.clamp(0.0, duration.max(0.0));
+        }
+        self.emit_playback(false);
+        Ok(())
+    }
+
+    fn next(&self) -> Result<()> {
+        {
+            let mut state = self.state();
+            let next = state.queue.position() + 1;
+            if state.jump_to(next).is_err() {
+                return Ok(());
+            }
+        }
+        self.emit_playback(true);
+        Ok(())
+    }
+
+    fn previous(&self) -> Result<()> {
+        let track_changed = {
+            let mut state = self.state();
+            // Like the native players, restart the current item unless we are near its start.
+            let position = state.queue.position();
+            if state.current_time > 3.0 || position == 0 {
+                state.current_time = 0.0;
+                false
+            } else {
+                state.jump_to(position - 1).is_ok()
+            }
+        };
+        self.emit_playback(track_changed);
+        Ok(())
+    }
+
+    fn skip_to_item(&self, track_id: String, start_playing: bool) -> Result<()> {
+        {
+            let mut state = self.state();
+            state.queue.jump_to_track(&track_id)?;
+            state.current_time = 0.0;
+            if start_playing {
+                state.playing = true;
+                state.paused = false;
+            }
+        }
+        self.emit_playback(true);
+        Ok(())
+    }
+
+    fn set_volume(&self, volume: f64) -> Result<()> {
+        self.state().volume = volume.clamp(0.0, 1.0);
+        self.emit_playback(false);
+        Ok(())
+    }
+
+    fn set_shuffle_mode(&self, mode: ShuffleMode) -> Result<()> {
+        self.state().shuffle_mode = mode;
+        self.emit_playback(false);
+        Ok(())
+    }
+
+    fn get_shuffle_mode(&self) -> Result<ShuffleMode> {
+        Ok(self.state().shuffle_mode.clone())
+    }
+
+    fn set_repeat_mode(&self, mode: RepeatMode) -> Result<()> {
+        self.state().repeat_mode = mode;
+        self.emit_playback(false);
+        Ok(())
+    }
+
+    fn get_repeat_mode(&self) -> Result<RepeatMode> {
+        Ok(self.state().repeat_mode.clone())
+    }
+
+    fn set_queue(
+        &self,
+        tracks: Vec<MusicKitTrack>,
+        start_playing: bool,
+        start_position: usize,
+    ) -> Result<QueueResponse> {
+        let response = self.mutate_queue(|state| {
+            state.queue.set(tracks, start_position)?;
+            state.current_time = 0.0;
+            state.playing = start_playing && !state.queue.is_empty();
+            state.paused = false;
+            Ok(())
+        })?;
+        self.emit_playback(true);
+        Ok(response)
+    }
+
+    fn set_queue_from_container(&self, container: QueueContainer) -> Result<QueueResponse> {
+        // The mock has no station stream to play
+        if container.kind == ContainerKind::Station {
+            return Err(Error::PlatformNotSupported);
+        }
+        let response = self.mutate_queue(|state| {
+            state.queue.set(container.tracks, container.start_index)?;
+            state.shuffle_mode = if container.shuffle {
+                ShuffleMode::Songs
+            } else {
+                ShuffleMode::Off
+            };
+            state.current_time = 0.0;
+            state.playing = !state.queue.is_empty();
+            state.paused = false;
+            Ok(())
+        })?;
+        self.emit_playback(true);
+        Ok(response)
+    }
+
+    fn update_queue(&self, tracks: Vec<MusicKitTrack>) -> Result<QueueResponse> {
+        self.mutate_queue(|state| {
+            state.edit_queue(|queue| {
+                queue.update(tracks);
+                Ok(())
+            })
+        })
+    }
+
+    fn insert_track_at_position(
+        &self,
+        track: MusicKitTrack,
+        position: usize,
+    ) -> Result<QueueResponse> {
+        self.insert_tracks_at_position(vec![track], position)
+    }
+
+    fn insert_tracks_at_position(
+        &self,
+        tracks: Vec<MusicKitTrack>,
+        position: usize,
+    ) -> Result<QueueResponse> {
+        self.mutate_queue(|state| state.edit_queue(|queue| queue.insert(position, tracks)))
+    }
+
+    fn remove_track_from_queue(&self, track_id: String) -> Result<QueueResponse> {
+        self.mutate_queue(|state| {
+            state.edit_queue(|queue| queue.remove_track(&track_id))?;
+            Ok(())
+        })
+    }
+
+    fn remove_track_at_position(&self, position: usize) -> Result<QueueResponse> {
+        self.mutate_queue(|state| {
+            state.edit_queue(|queue| queue.remove_at(position))?;
+            Ok(())
+        })
+    }
+
+    fn remove_range(&self, start: usize, end: usize) -> Result<QueueResponse> {
+        self.mutate_queue(|state| {
+            state.edit_queue(|queue| queue.remove_range(start, end))?;
+            Ok(())
+        })
+    }
+
+    fn clear_queue(&self) -> Result<QueueResponse> {
+        let response = self.mutate_queue(|state| {
+            state.queue.clear();
+            state.playing = false;
+            state.paused = false;
+            state.current_time = 0.0;
+            Ok(())
+        })?;
+        self.emit_playback(false);
+        Ok(response)
+    }
+
+    fn insert_track_next(&self, track: MusicKitTrack) -> Result<QueueResponse> {
+        self.mutate_queue(|state| {
+            state.queue.insert_next(vec![track]);
+            Ok(())
+        })
+    }
+
+    fn insert_track_last(&self, track: MusicKitTrack) -> Result<QueueResponse> {
+        self.append_tracks_to_queue(vec![track])
+    }
+
+    fn append_tracks_to_queue(&self, tracks: Vec<MusicKitTrack>) -> Result<QueueResponse> {
+        self.mutate_queue(|state| {
+            state.queue.append(tracks);
+            Ok(())
+        })
+    }
+
+    fn move_queue_item(&self, from: usize, to: usize) -> Result<QueueResponse> {
+        self.move_queue_items(vec![from], to)
+    }
+
+    fn move_queue_items(&self, indices: Vec<usize>, to: usize) -> Result<QueueResponse> {
+        self.mutate_queue(|state| state.edit_queue(|queue| queue.move_items(&indices, to)))
+    }
+
+    fn get_current_track(&self) -> Result<Option<MusicKitTrack>> {
+        Ok(self.state().current_track().cloned())
+    }
+
+    fn get_playback_state(&self) -> Result<StateUpdateEvent> {
+        Ok(self.state().playback_state())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{sync::mpsc, time::Duration};
+
+    use tauri::{
+        test::{mock_app, MockRuntime},
+        App, Listener,
+    };
+
+    use super::*;
+    use crate::events::*;
+
+    fn mock() -> (App<MockRuntime>, MockMusicKit<MockRuntime>) {
+        let app = mock_app();
+        let mock = MockMusicKit::new(app.handle().clone());
+        (app, mock)
+    }
+
+    /// Collects the payloads of `event` emitted on `app`.
+    fn record(app: &App<MockRuntime>, event: &str) -> mpsc::Receiver<serde_json::Value> {
+        let (sender, receiver) = mpsc::channel();
+        app.listen_any(event, move |emitted| {
+            let _ = sender.send(serde_json::from_str(emitted.payload()).unwrap());
+        });
+        receiver
+    }
+
+    fn track(id: &str, duration: f64) -> MusicKitTrack {
+        MusicKitTrack {
+            id: id.to_string(),
+            duration,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn seek_clamps_to_the_current_track() {
+        let (_app, mock) = mock();
+        mock.set_queue(vec![track("a", 10.0)], false, 0).unwrap();
+        mock.seek(25.0).unwrap();
+        assert_eq!(mock.get_playback_state().unwrap().current_time, 10.0);
+        mock.seek(-3.0).unwrap();
+        assert_eq!(mock.get_playback_state().unwrap().current_time, 0.0);
+    }
+
+    #[test]
+    fn seek_tolerates_a_negative_duration() {
+        let (_app, mock) = mock();
+        mock.set_queue(vec![track("a", -1.0)], false, 0).unwrap();
+        mock.seek(5.0).unwrap();
+        assert_eq!(mock.get_playback_state().unwrap().current_time, 0.0);
+    }
+
+    #[test]
+    fn failed_authorization_reports_the_error_until_a_result_is_set() {
+        let (app, mock) = mock();
+        let changes = record(&app, PLAYER_ADAPTER_EVENTS_AUTHORIZATION_STATUS_CHANGE);
+
+        mock.fail_authorization("subscription required");
+        let response = mock.authorize().unwrap();
+        assert_eq!(response.status, AuthorizationStatus::Error);
+        assert_eq!(response.error.as_deref(), Some("subscription required"));
+        assert_eq!(
+            mock.get_authorization_status().unwrap().status,
+            AuthorizationStatus::NotDetermined
+        );
+        assert_eq!(mock.get_user_token().unwrap().token, None);
+        assert!(changes.try_recv().is_err());
+
+        mock.set_authorization_result(AuthorizationStatus::Authorized);
+        assert!(mock.authorize().unwrap().status.is_authorized());
+        let change = changes.recv_timeout(Duration::from_secs(1)).unwrap();
+        assert_eq!(change, serde_json::json!({ "status": "authorized" }));
+    }
+
+    #[test]
+    fn injected_errors_are_emitted_as_player_errors() {
+        let (app, mock) = mock();
+        let errors = record(&app, PLAYER_ADAPTER_EVENTS_ERROR);
+
+        mock.inject_error(ErrorEvent {
+            error: "Playback failed".to_string(),
+            code: Some("CONTENT_UNAVAILABLE".to_string()),
+        });
+        let error = errors.recv_timeout(Duration::from_secs(1)).unwrap();
+        assert_eq!(
+            error,
+            serde_json::json!({ "error": "Playback failed", "code": "CONTENT_UNAVAILABLE" })
+        );
+    }
+}
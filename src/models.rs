@@ -1,40 +1,203 @@
 use serde::{Deserialize, Serialize};
 
+use crate::{Artwork, ArtworkFormat, Error, Result};
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct GetUserTokenResponse {
     pub token: Option<String>,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
-#[serde(rename_all = "camelCase")]
+/// A track as reported by the native player, kept in the queue and converted from
+/// Apple Music API songs.
+///
+/// `id` is the identifier the player plays the track with. `catalog_id` and
+/// `library_id` carry the catalog and library identifiers when they are known.
+///
+/// Payloads in the [`MusicKitTrackData`] shape (`artistName`, `albumName`,
+/// `genreNames`, `artwork`, `durationInMillis`) deserialize as well, and so does an
+/// `artworkUrl` string. Fields not modelled here are kept in `extra` and serialized
+/// back unchanged.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", from = "TrackPayload")]
 pub struct MusicKitTrack {
     pub id: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub catalog_id: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub library_id: Option<String>,
     pub title: String,
     pub artist: String,
     pub album: String,
+    pub genres: Vec<String>,
+    /// The duration in seconds.
     pub duration: f64,
-    pub artwork_url: Option<String>,
+    pub artwork: Option<Artwork>,
     pub is_explicit: bool,
     pub is_playable: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub content_rating: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub isrc: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub track_number: Option<u32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub disc_number: Option<u32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub release_date: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub composer: Option<String>,
+    #[serde(flatten)]
+    pub extra: serde_json::Map<String, serde_json::Value>,
 }
 
 impl Default for MusicKitTrack {
     fn default() -> Self {
         Self {
             id: String::new(),
+            catalog_id: None,
+            library_id: None,
             title: String::new(),
             artist: String::new(),
             album: String::new(),
+            genres: Vec::new(),
             duration: 0.0,
-            artwork_url: None,
+            artwork: None,
             is_explicit: false,
             is_playable: true,
+            content_rating: None,
+            isrc: None,
+            track_number: None,
+            disc_number: None,
+            release_date: None,
+            composer: None,
+            extra: serde_json::Map::new(),
         }
     }
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+impl MusicKitTrack {
+    /// The duration in whole milliseconds.
+    pub fn duration_in_millis(&self) -> i64 {
+        (self.duration * 1000.0).round() as i64
+    }
+
+    /// The artwork rendered at `width` x `height` pixels, when the track has artwork.
+    pub fn artwork_url(&self, width: u32, height: u32) -> Option<String> {
+        self.artwork
+            .as_ref()
+            .map(|artwork| artwork.url(width, height, ArtworkFormat::Jpg))
+    }
+
+    /// The genres joined the way `genreNames` reports them.
+    pub fn genre_names(&self) -> String {
+        self.genres.join(GENRE_SEPARATOR)
+    }
+}
+
+const GENRE_SEPARATOR: &str = ", ";
+
+fn split_genres(genre_names: &str) -> Vec<String> {
+    genre_names
+        .split(',')
+        .map(str::trim)
+        .filter(|genre| !genre.is_empty())
+        .map(str::to_string)
+        .collect()
+}
+
+/// Both track shapes the native side has reported.
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct TrackPayload {
+    id: String,
+    catalog_id: Option<String>,
+    library_id: Option<String>,
+    #[serde(default)]
+    title: String,
+    #[serde(default, alias = "artistName")]
+    artist: String,
+    #[serde(default, alias = "albumName")]
+    album: String,
+    #[serde(alias = "genreNames")]
+    genres: Option<Genres>,
+    duration: Option<f64>,
+    duration_in_millis: Option<f64>,
+    artwork: Option<ArtworkPayload>,
+    artwork_url: Option<String>,
+    is_explicit: Option<bool>,
+    is_playable: Option<bool>,
+    content_rating: Option<String>,
+    isrc: Option<String>,
+    track_number: Option<u32>,
+    disc_number: Option<u32>,
+    release_date: Option<String>,
+    #[serde(alias = "composerName")]
+    composer: Option<String>,
+    #[serde(flatten)]
+    extra: serde_json::Map<String, serde_json::Value>,
+}
+
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum ArtworkPayload {
+    Url(String),
+    Artwork(Artwork),
+}
+
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum Genres {
+    List(Vec<String>),
+    Joined(String),
+}
+
+impl From<TrackPayload> for MusicKitTrack {
+    fn from(payload: TrackPayload) -> Self {
+        let duration = payload
+            .duration
+            .or(payload.duration_in_millis.map(|millis| millis / 1000.0))
+            .unwrap_or_default();
+        let artwork = match payload.artwork {
+            Some(ArtworkPayload::Artwork(artwork)) => Some(artwork),
+            Some(ArtworkPayload::Url(url)) => Some(Artwork::new(url)),
+            None => payload.artwork_url.map(Artwork::new),
+        }
+        .filter(|artwork| !artwork.url.is_empty());
+        Self {
+            id: payload.id,
+            catalog_id: payload.catalog_id,
+            library_id: payload.library_id,
+            title: payload.title,
+            artist: payload.artist,
+            album: payload.album,
+            genres: match payload.genres {
+                None => Vec::new(),
+                Some(Genres::List(genres)) => genres,
+                Some(Genres::Joined(genre_names)) => split_genres(&genre_names),
+            },
+            duration,
+            artwork,
+            is_explicit: payload
+                .is_explicit
+                .unwrap_or(payload.content_rating.as_deref() == Some("explicit")),
+            is_playable: payload.is_playable.unwrap_or(true),
+            content_rating: payload.content_rating,
+            isrc: payload.isrc,
+            track_number: payload.track_number,
+            disc_number: payload.disc_number,
+            release_date: payload.release_date,
+            composer: payload.composer,
+            extra: payload.extra,
+        }
+    }
+}
+
+/// The track shape state updates used to carry.
+///
+/// State updates now carry a [`MusicKitTrack`]. The conversions between the two keep
+/// every field `MusicKitTrackData` has.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct MusicKitTrackData {
     pub id: String,
@@ -46,10 +209,51 @@ pub struct MusicKitTrackData {
     pub duration_in_millis: i32,
 }
 
+impl From<MusicKitTrackData> for MusicKitTrack {
+    fn from(data: MusicKitTrackData) -> Self {
+        Self {
+            id: data.id,
+            title: data.title,
+            artist: data.artist_name,
+            album: data.album_name,
+            genres: split_genres(&data.genre_names),
+            duration: f64::from(data.duration_in_millis) / 1000.0,
+            artwork: Some(data.artwork)
+                .filter(|url| !url.is_empty())
+                .map(Artwork::new),
+            ..Default::default()
+        }
+    }
+}
+
+impl From<&MusicKitTrack> for MusicKitTrackData {
+    fn from(track: &MusicKitTrack) -> Self {
+        Self {
+            id: track.id.clone(),
+            title: track.title.clone(),
+            artist_name: track.artist.clone(),
+            album_name: track.album.clone(),
+            genre_names: track.genre_names(),
+            artwork: track
+                .artwork
+                .as_ref()
+                .map(|artwork| artwork.url.clone())
+                .unwrap_or_default(),
+            duration_in_millis: track.duration_in_millis() as i32,
+        }
+    }
+}
+
+impl From<MusicKitTrack> for MusicKitTrackData {
+    fn from(track: MusicKitTrack) -> Self {
+        Self::from(&track)
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct AuthorizationResponse {
-    pub status: String, // "authorized", "notAuthorized", "error"
+    pub status: AuthorizationStatus,
     pub error: Option<String>,
 }
 
@@ -63,7 +267,7 @@ pub struct UnauthorizeResponse {
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct AuthorizationStatusResponse {
-    pub status: String, // "authorized", "notAuthorized", "notInitialized"
+    pub status: AuthorizationStatus,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -73,11 +277,76 @@ pub struct QueueResponse {
     pub position: usize,
 }
 
+/// The `{ success, error }` shape queue operations used to return.
+///
+/// Queue operations now return `Result<QueueResponse>`. Convert their results with
+/// `QueueOperationResponse::from` where the old shape is still expected.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct QueueOperationResponse {
     pub success: bool,
     pub error: Option<String>,
+    /// The queue after the operation, when it succeeded.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub queue: Option<QueueResponse>,
+}
+
+impl QueueOperationResponse {
+    /// Turns a reported failure into an error.
+    pub fn into_result(self) -> Result<Option<QueueResponse>> {
+        if self.success {
+            Ok(self.queue)
+        } else {
+            Err(Error::MusicKitError(self.error.unwrap_or_default()))
+        }
+    }
+}
+
+impl From<Result<QueueResponse>> for QueueOperationResponse {
+    fn from(result: Result<QueueResponse>) -> Self {
+        match result {
+            Ok(queue) => Self {
+                success: true,
+                error: None,
+                queue: Some(queue),
+            },
+            Err(error) => Self {
+                success: false,
+                error: Some(error.to_string()),
+                queue: None,
+            },
+        }
+    }
+}
+
+/// The kind of catalog container a queue can be built from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum ContainerKind {
+    Album,
+    Playlist,
+    /// An artist's top songs.
+    Artist,
+    Station,
+}
+
+/// A queue built from a whole album, playlist, artist or station.
+///
+/// The plugin resolves the container's tracks and hands the backend this one
+/// descriptor, so native players can queue the container itself instead of a list
+/// of track ids.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct QueueContainer {
+    pub kind: ContainerKind,
+    pub id: String,
+    /// The container's tracks in their original order. Empty for stations, whose
+    /// tracks are only known to the player.
+    pub tracks: Vec<MusicKitTrack>,
+    /// The track to start at, as an index into `tracks`.
+    pub start_index: usize,
+    /// Whether to play the tracks in shuffled order.
+    pub shuffle: bool,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -85,13 +354,14 @@ pub struct QueueOperationResponse {
 pub struct StateUpdateEvent {
     pub playing: bool,
     pub paused: bool,
-    pub current_track: Option<MusicKitTrackData>,
+    pub current_track: Option<MusicKitTrack>,
     pub current_time: f64,
     pub duration: f64,
-    pub progress: f64, // Add progress calculation
+    /// `current_time` as a fraction of `duration`, or `0.0` when the duration is unknown.
+    pub progress: f64,
     pub queue_position: usize,
-    pub shuffle_mode: String,
-    pub repeat_mode: String,
+    pub shuffle_mode: ShuffleMode,
+    pub repeat_mode: RepeatMode,
     pub volume: f64,
 }
 
@@ -102,6 +372,15 @@ pub struct QueueUpdateEvent {
     pub position: usize,
 }
 
+/// The queue restored from the previous session.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct QueueRestoredEvent {
+    pub items: Vec<MusicKitTrack>,
+    pub position: usize,
+    pub current_time: f64,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct TrackChangeEvent {
@@ -114,3 +393,291 @@ pub struct ErrorEvent {
     pub error: String,
     pub code: Option<String>,
 }
+
+/// Whether the app may access the user's Apple Music library and playback.
+///
+/// Values the native side reports that are not modelled here are kept in `Unknown`
+/// and serialized back unchanged.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(from = "String", into = "String")]
+pub enum AuthorizationStatus {
+    Authorized,
+    NotAuthorized,
+    Denied,
+    Restricted,
+    #[default]
+    NotDetermined,
+    NotInitialized,
+    Error,
+    Unknown(String),
+}
+
+impl AuthorizationStatus {
+    pub fn as_str(&self) -> &str {
+        match self {
+            AuthorizationStatus::Authorized => "authorized",
+            AuthorizationStatus::NotAuthorized => "notAuthorized",
+            AuthorizationStatus::Denied => "denied",
+            AuthorizationStatus::Restricted => "restricted",
+            AuthorizationStatus::NotDetermined => "notDetermined",
+            AuthorizationStatus::NotInitialized => "notInitialized",
+            AuthorizationStatus::Error => "error",
+            AuthorizationStatus::Unknown(value) => value,
+        }
+    }
+
+    pub fn is_authorized(&self) -> bool {
+        *self == AuthorizationStatus::Authorized
+    }
+}
+
+impl From<&str> for AuthorizationStatus {
+    fn from(value: &str) -> Self {
+        match value {
+            "authorized" => AuthorizationStatus::Authorized,
+            "notAuthorized" => AuthorizationStatus::NotAuthorized,
+            "denied" => AuthorizationStatus::Denied,
+            "restricted" => AuthorizationStatus::Restricted,
+            "notDetermined" => AuthorizationStatus::NotDetermined,
+            "notInitialized" => AuthorizationStatus::NotInitialized,
+            "error" => AuthorizationStatus::Error,
+            other => AuthorizationStatus::Unknown(other.to_string()),
+        }
+    }
+}
+
+impl From<String> for AuthorizationStatus {
+    fn from(value: String) -> Self {
+        value.as_str().into()
+    }
+}
+
+impl From<AuthorizationStatus> for String {
+    fn from(status: AuthorizationStatus) -> Self {
+        match status {
+            AuthorizationStatus::Unknown(value) => value,
+            status => status.as_str().to_string(),
+        }
+    }
+}
+
+/// How the player orders the queue.
+///
+/// The iOS player reports shuffling as `"on"`, which reads as [`ShuffleMode::Songs`].
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(from = "String", into = "String")]
+pub enum ShuffleMode {
+    #[default]
+    Off,
+    Songs,
+    Albums,
+    Unknown(String),
+}
+
+impl ShuffleMode {
+    pub fn as_str(&self) -> &str {
+        match self {
+            ShuffleMode::Off => "off",
+            ShuffleMode::Songs => "songs",
+            ShuffleMode::Albums => "albums",
+            ShuffleMode::Unknown(value) => value,
+        }
+    }
+}
+
+impl From<&str> for ShuffleMode {
+    fn from(value: &str) -> Self {
+        match value {
+            "off" => ShuffleMode::Off,
+            "songs" | "on" => ShuffleMode::Songs,
+            "albums" => ShuffleMode::Albums,
+            other => ShuffleMode::Unknown(other.to_string()),
+        }
+    }
+}
+
+impl From<String> for ShuffleMode {
+    fn from(value: String) -> Self {
+        value.as_str().into()
+    }
+}
+
+impl From<ShuffleMode> for String {
+    fn from(mode: ShuffleMode) -> Self {
+        match mode {
+            ShuffleMode::Unknown(value) => value,
+            mode => mode.as_str().to_string(),
+        }
+    }
+}
+
+/// Whether the player repeats the current item or the whole queue.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(from = "String", into = "String")]
+pub enum RepeatMode {
+    #[default]
+    None,
+    One,
+    All,
+    Unknown(String),
+}
+
+impl RepeatMode {
+    pub fn as_str(&self) -> &str {
+        match self {
+            RepeatMode::None => "none",
+            RepeatMode::One => "one",
+            RepeatMode::All => "all",
+            RepeatMode::Unknown(value) => value,
+        }
+    }
+}
+
+impl From<&str> for RepeatMode {
+    fn from(value: &str) -> Self {
+        match value {
+            "none" | "off" => RepeatMode::None,
+            "one" => RepeatMode::One,
+            "all" => RepeatMode::All,
+            other => RepeatMode::Unknown(other.to_string()),
+        }
+    }
+}
+
+impl From<String> for RepeatMode {
+    fn from(value: String) -> Self {
+        value.as_str().into()
+    }
+}
+
+impl From<RepeatMode> for String {
+    fn from(mode: RepeatMode) -> Self {
+        match mode {
+            RepeatMode::Unknown(value) => value,
+            mode => mode.as_str().to_string(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    #[test]
+    fn shuffle_modes_round_trip_through_their_names() {
+        for (name, mode) in [
+            ("off", ShuffleMode::Off),
+            ("songs", ShuffleMode::Songs),
+            ("albums", ShuffleMode::Albums),
+            ("random", ShuffleMode::Unknown("random".to_string())),
+        ] {
+            assert_eq!(
+                serde_json::from_value::<ShuffleMode>(json!(name)).unwrap(),
+                mode
+            );
+            assert_eq!(serde_json::to_value(&mode).unwrap(), json!(name));
+        }
+        assert_eq!(ShuffleMode::from("on"), ShuffleMode::Songs);
+    }
+
+    #[test]
+    fn legacy_track_payloads_decode() {
+        let track: MusicKitTrack = serde_json::from_value(json!({
+            "id": "1",
+            "title": "Song",
+            "artistName": "Artist",
+            "albumName": "Album",
+            "genreNames": "Pop, Rock,",
+            "durationInMillis": 181500,
+            "artworkUrl": "https://example.com/{w}x{h}bb.jpg",
+            "composerName": "Composer",
+        }))
+        .unwrap();
+
+        assert_eq!(track.artist, "Artist");
+        assert_eq!(track.album, "Album");
+        assert_eq!(track.genres, ["Pop", "Rock"]);
+        assert_eq!(track.duration, 181.5);
+        assert_eq!(
+            track.artwork.as_ref().map(|artwork| artwork.url.as_str()),
+            Some("https://example.com/{w}x{h}bb.jpg")
+        );
+        assert_eq!(track.composer.as_deref(), Some("Composer"));
+        assert!(track.is_playable);
+        assert!(track.extra.is_empty());
+    }
+
+    #[test]
+    fn track_data_payloads_decode_and_round_trip() {
+        let data = MusicKitTrackData {
+            id: "1".to_string(),
+            title: "Song".to_string(),
+            artist_name: "Artist".to_string(),
+            album_name: "Album".to_string(),
+            genre_names: "Pop, Rock".to_string(),
+            artwork: "https://example.com/{w}x{h}bb.jpg".to_string(),
+            duration_in_millis: 181500,
+        };
+
+        let track: MusicKitTrack =
+            serde_json::from_value(serde_json::to_value(&data).unwrap()).unwrap();
+
+        assert_eq!(track, MusicKitTrack::from(data.clone()));
+        assert_eq!(MusicKitTrackData::from(&track), data);
+    }
+
+    #[test]
+    fn current_track_fields_win_over_legacy_ones() {
+        let track: MusicKitTrack = serde_json::from_value(json!({
+            "id": "1",
+            "genres": ["Jazz"],
+            "duration": 2.0,
+            "durationInMillis": 5000,
+            "artwork": { "url": "https://example.com/a.jpg" },
+            "artworkUrl": "https://example.com/b.jpg",
+        }))
+        .unwrap();
+
+        assert_eq!(track.genres, ["Jazz"]);
+        assert_eq!(track.duration, 2.0);
+        assert_eq!(
+            track.artwork.map(|artwork| artwork.url),
+            Some("https://example.com/a.jpg".to_string())
+        );
+    }
+
+    #[test]
+    fn unknown_track_fields_are_kept() {
+        let track: MusicKitTrack = serde_json::from_value(json!({
+            "id": "1",
+            "artwork": "",
+            "contentRating": "explicit",
+            "playParams": { "kind": "song" },
+        }))
+        .unwrap();
+
+        assert!(track.artwork.is_none());
+        assert!(track.is_explicit);
+        assert_eq!(
+            serde_json::to_value(&track).unwrap()["playParams"],
+            json!({ "kind": "song" })
+        );
+    }
+
+    #[test]
+    fn native_authorization_statuses_are_recognized() {
+        for (name, status) in [
+            ("authorized", AuthorizationStatus::Authorized),
+            ("notAuthorized", AuthorizationStatus::NotAuthorized),
+            ("denied", AuthorizationStatus::Denied),
+            ("restricted", AuthorizationStatus::Restricted),
+            ("notDetermined", AuthorizationStatus::NotDetermined),
+        ] {
+            let response: AuthorizationStatusResponse =
+                serde_json::from_value(json!({ "status": name })).unwrap();
+            assert_eq!(response.status, status);
+        }
+    }
+}
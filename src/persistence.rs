@@ -0,0 +1,306 @@
+//! Saving the queue to the app data directory and restoring it on the next launch.
+//!
+//! The queue is stored as versioned JSON. When the stored model changes, bump
+//! [`STORAGE_VERSION`] and teach [`decode`] to migrate the older versions.
+
+use std::{
+    fs,
+    path::{Path, PathBuf},
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        mpsc::{self, RecvTimeoutError},
+        Arc,
+    },
+    thread,
+    time::{Duration, Instant, SystemTime, UNIX_EPOCH},
+};
+
+use serde::{Deserialize, Serialize};
+
+use crate::{models::*, Error, MusicKitBackend, Result};
+
+/// The version written by this build.
+pub const STORAGE_VERSION: u32 = 1;
+
+/// How long queue changes are coalesced before being written by default.
+pub const DEFAULT_PERSIST_DEBOUNCE: Duration = Duration::from_millis(1000);
+
+const FILE_NAME: &str = "musickit-queue.json";
+
+/// The queue as written to disk.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PersistedQueue {
+    pub version: u32,
+    pub queue: QueueResponse,
+    pub current_time: f64,
+    /// Seconds since the Unix epoch.
+    pub saved_at: u64,
+}
+
+#[derive(Deserialize)]
+struct StoredVersion {
+    version: u32,
+}
+
+/// Parses stored bytes, migrating older versions to the current model.
+///
+/// Returns `None` for versions written by a newer build, and fails for versions no
+/// build ever wrote.
+pub fn decode(bytes: &[u8]) -> Result<Option<PersistedQueue>> {
+    let invalid = |error: serde_json::Error| Error::InvalidStoredQueue(error.to_string());
+    let StoredVersion { version } = serde_json::from_slice(bytes).map_err(invalid)?;
+    match version {
+        // Migrate each older version here, by parsing its own model and converting it
+        // to `PersistedQueue`.
+        STORAGE_VERSION => serde_json::from_slice(bytes).map(Some).map_err(invalid),
+        newer if newer > STORAGE_VERSION => Ok(None),
+        unknown => Err(Error::InvalidStoredQueue(format!(
+            "unknown storage version {unknown}"
+        ))),
+    }
+}
+
+/// Saves the queue in the background and restores it once on initialize.
+pub(crate) struct QueuePersistence {
+    path: PathBuf,
+    restore: bool,
+    /// Set once restoring is done, so an empty startup queue cannot overwrite the save.
+    ready: Arc<AtomicBool>,
+    restored: AtomicBool,
+    /// Whether the last state update was playing.
+    playing: AtomicBool,
+    saves: mpsc::Sender<()>,
+    on_restore: Box<dyn Fn(&QueueRestoredEvent) + Send + Sync>,
+}
+
+impl QueuePersistence {
+    /// Starts the writer thread. Nothing is saved before [`QueuePersistence::restore`]
+    /// ran when `restore` is set.
+    pub(crate) fn start<F>(
+        data_dir: &Path,
+        backend: Arc<dyn MusicKitBackend>,
+        debounce: Duration,
+        restore: bool,
+        on_restore: F,
+    ) -> Self
+    where
+        F: Fn(&QueueRestoredEvent) + Send + Sync + 'static,
+    {
+        let path = data_dir.join(FILE_NAME);
+        let ready = Arc::new(AtomicBool::new(!restore));
+        let (saves, requests) = mpsc::channel();
+        thread::spawn({
+            let path = path.clone();
+            let ready = ready.clone();
+            move || {
+                while requests.recv().is_ok() {
+                    // Coalesce every request arriving within the debounce window.
+                    let deadline = Instant::now() + debounce;
+                    let mut closed = false;
+                    while let Some(remaining) = deadline.checked_duration_since(Instant::now()) {
+                        match requests.recv_timeout(remaining) {
+                            Ok(()) => {}
+                            Err(RecvTimeoutError::Timeout) => break,
+                            Err(RecvTimeoutError::Disconnected) => {
+                                closed = true;
+                                break;
+                            }
+                        }
+                    }
+                    if ready.load(Ordering::Acquire) {
+                        if let Err(error) = save(&path, backend.as_ref()) {
+                            log::warn!("failed to save the queue: {error}");
+                        }
+                    }
+                    if closed {
+                        break;
+                    }
+                }
+            }
+        });
+        Self {
+            path,
+            restore,
+            ready,
+            restored: AtomicBool::new(false),
+            playing: AtomicBool::new(false),
+            saves,
+            on_restore: Box::new(on_restore),
+        }
+    }
+
+    /// Requests a save once the debounce window passed.
+    pub(crate) fn schedule_save(&self) {
+        let _ = self.saves.send(());
+    }
+
+    /// Requests a save when playback pauses or stops, recording where it stopped.
+    pub(crate) fn playback_changed(&self, playing: bool) {
+        if self.playing.swap(playing, Ordering::AcqRel) && !playing {
+            self.schedule_save();
+        }
+    }
+
+    /// Restores the saved queue without starting playback, the first time it is called.
+    pub(crate) fn restore(&self, backend: &dyn MusicKitBackend) -> Result<()> {
+        if !self.restore || self.restored.swap(true, Ordering::AcqRel) {
+            return Ok(());
+        }
+        let result = self.load().and_then(|saved| match saved {
+            Some(saved) if !saved.queue.items.is_empty() => {
+                let QueueResponse { items, position } = saved.queue;
+                backend.set_queue(items.clone(), false, position)?;
+                if saved.current_time > 0.0 {
+                    backend.seek(saved.current_time)?;
+                }
+                (self.on_restore)(&QueueRestoredEvent {
+                    items,
+                    position,
+                    current_time: saved.current_time,
+                });
+                Ok(())
+            }
+            _ => Ok(()),
+        });
+        self.ready.store(true, Ordering::Release);
+        result
+    }
+
+    fn load(&self) -> Result<Option<PersistedQueue>> {
+        load(&self.path)
+    }
+}
+
+/// Reads the save at `path`, treating a missing or unreadable save as no save.
+fn load(path: &Path) -> Result<Option<PersistedQueue>> {
+    match fs::read(path) {
+        Ok(bytes) => decode(&bytes).or_else(|error| {
+            log::warn!("ignoring the saved queue: {error}");
+            Ok(None)
+        }),
+        Err(error) if error.kind() == std::io::ErrorKind::NotFound => Ok(None),
+        Err(error) => Err(Error::Io(error.to_string())),
+    }
+}
+
+fn save(path: &Path, backend: &dyn MusicKitBackend) -> Result<()> {
+    let persisted = PersistedQueue {
+        version: STORAGE_VERSION,
+        queue: backend.get_queue()?,
+        current_time: backend.get_playback_state()?.current_time,
+        saved_at: SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_secs(),
+    };
+    let bytes = serde_json::to_vec(&persisted)
+        .map_err(|error| Error::InvalidStoredQueue(error.to_string()))?;
+    let io = |error: std::io::Error| Error::Io(error.to_string());
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir).map_err(io)?;
+    }
+    // Write a sibling file first so a crash never leaves a truncated save behind.
+    let partial = path.with_extension("json.partial");
+    fs::write(&partial, bytes).map_err(io)?;
+    fs::rename(&partial, path).map_err(io)
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    fn saved_queue() -> PersistedQueue {
+        PersistedQueue {
+            version: STORAGE_VERSION,
+            queue: QueueResponse {
+                items: vec![MusicKitTrack {
+                    id: "1".to_string(),
+                    title: "Song".to_string(),
+                    duration: 180.0,
+                    ..Default::default()
+                }],
+                position: 0,
+            },
+            current_time: 42.5,
+            saved_at: 1_700_000_000,
+        }
+    }
+
+    /// A directory of its own under the system temp directory.
+    fn temp_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!(
+            "musickit-persistence-{name}-{}",
+            std::process::id()
+        ));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    #[test]
+    fn saved_queues_round_trip() {
+        let bytes = serde_json::to_vec(&saved_queue()).unwrap();
+        let decoded = decode(&bytes).unwrap().unwrap();
+        assert_eq!(decoded.version, STORAGE_VERSION);
+        assert_eq!(decoded.queue.items.len(), 1);
+        assert_eq!(decoded.queue.items[0].id, "1");
+        assert_eq!(decoded.queue.items[0].title, "Song");
+        assert_eq!(decoded.queue.position, 0);
+        assert_eq!(decoded.current_time, 42.5);
+        assert_eq!(decoded.saved_at, 1_700_000_000);
+    }
+
+    #[test]
+    fn saves_from_a_newer_build_are_skipped() {
+        let mut saved = serde_json::to_value(saved_queue()).unwrap();
+        saved["version"] = json!(STORAGE_VERSION + 1);
+        assert!(decode(saved.to_string().as_bytes()).unwrap().is_none());
+
+        // A newer model need not parse as the current one.
+        let newer = json!({ "version": STORAGE_VERSION + 1, "tracks": [] });
+        assert!(decode(newer.to_string().as_bytes()).unwrap().is_none());
+    }
+
+    #[test]
+    fn unknown_versions_are_rejected() {
+        let mut saved = serde_json::to_value(saved_queue()).unwrap();
+        saved["version"] = json!(0);
+        assert!(matches!(
+            decode(saved.to_string().as_bytes()),
+            Err(Error::InvalidStoredQueue(_))
+        ));
+    }
+
+    #[test]
+    fn corrupt_saves_are_rejected() {
+        for bytes in [
+            &b"{\"version\": 1, \"queue\": "[..],
+            b"not json",
+            b"{}",
+            b"{\"version\": 1}",
+        ] {
+            assert!(
+                matches!(decode(bytes), Err(Error::InvalidStoredQueue(_))),
+                "{}",
+                String::from_utf8_lossy(bytes)
+            );
+        }
+    }
+
+    #[test]
+    fn a_corrupt_or_missing_file_loads_as_no_save() {
+        let dir = temp_dir("load");
+        let path = dir.join(FILE_NAME);
+        assert!(load(&path).unwrap().is_none());
+
+        fs::write(&path, b"{\"version\": 1, \"queue\": {\"ite").unwrap();
+        assert!(load(&path).unwrap().is_none());
+
+        fs::write(&path, serde_json::to_vec(&saved_queue()).unwrap()).unwrap();
+        assert_eq!(load(&path).unwrap().unwrap().current_time, 42.5);
+        fs::remove_dir_all(dir).unwrap();
+    }
+}
@@ -0,0 +1,838 @@
+//! The playback queue, resolved in Rust for every backend.
+//!
+//! [`Queue`] owns the tracks and the cursor pointing at the current item. Every queue
+//! operation is implemented here once, so backends only apply the resulting track list
+//! and start position to their native player.
+
+use std::collections::VecDeque;
+
+use crate::{models::*, Error, Result};
+
+/// How many queue changes [`QueueHistory`] keeps by default.
+pub const DEFAULT_HISTORY_LIMIT: usize = 50;
+
+/// An ordered list of tracks with a cursor on the current item.
+///
+/// The cursor is always `0` for an empty queue and a valid index otherwise.
+#[derive(Debug, Clone, Default)]
+pub struct Queue {
+    items: Vec<MusicKitTrack>,
+    position: usize,
+}
+
+impl Queue {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Creates a queue with the cursor on `position`, clamped into range.
+    pub fn with_position(items: Vec<MusicKitTrack>, position: usize) -> Self {
+        let position = position.min(items.len().saturating_sub(1));
+        Self { items, position }
+    }
+
+    pub fn items(&self) -> &[MusicKitTrack] {
+        &self.items
+    }
+
+    /// The index of the current item.
+    pub fn position(&self) -> usize {
+        self.position
+    }
+
+    pub fn len(&self) -> usize {
+        self.items.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.items.is_empty()
+    }
+
+    pub fn current(&self) -> Option<&MusicKitTrack> {
+        self.items.get(self.position)
+    }
+
+    pub fn get(&self, index: usize) -> Option<&MusicKitTrack> {
+        self.items.get(index)
+    }
+
+    /// The index of the first item with `track_id`.
+    pub fn index_of(&self, track_id: &str) -> Option<usize> {
+        self.items.iter().position(|track| track.id == track_id)
+    }
+
+    /// Replaces the queue and moves the cursor to `start_position`.
+    pub fn set(&mut self, tracks: Vec<MusicKitTrack>, start_position: usize) -> Result<()> {
+        if !tracks.is_empty() && start_position >= tracks.len() {
+            return Err(Error::QueuePositionOutOfRange {
+                position: start_position,
+                len: tracks.len(),
+            });
+        }
+        self.items = tracks;
+        self.position = start_position.min(self.items.len().saturating_sub(1));
+        Ok(())
+    }
+
+    /// Replaces the queue, keeping the cursor on the current item if it is still
+    /// present and rewinding it to the start otherwise.
+    pub fn update(&mut self, tracks: Vec<MusicKitTrack>) {
+        let position = self
+            .current()
+            .and_then(|current| tracks.iter().position(|track| track.id == current.id))
+            .unwrap_or(0);
+        self.items = tracks;
+        self.position = position;
+    }
+
+    /// Moves the cursor to `index`.
+    pub fn jump_to(&mut self, index: usize) -> Result<()> {
+        self.check_index(index)?;
+        self.position = index;
+        Ok(())
+    }
+
+    /// Moves the cursor to the first item with `track_id`.
+    pub fn jump_to_track(&mut self, track_id: &str) -> Result<()> {
+        let index = self
+            .index_of(track_id)
+            .ok_or_else(|| Error::TrackNotInQueue(track_id.to_string()))?;
+        self.position = index;
+        Ok(())
+    }
+
+    /// Inserts `tracks` before `position`, or at the end when `position` is the length.
+    ///
+    /// Items inserted at or before the cursor push the current item back, so the cursor
+    /// keeps pointing at it.
+    pub fn insert(&mut self, position: usize, tracks: Vec<MusicKitTrack>) -> Result<()> {
+        if position > self.items.len() {
+            return Err(Error::QueuePositionOutOfRange {
+                position,
+                len: self.items.len(),
+            });
+        }
+        let count = tracks.len();
+        let was_empty = self.items.is_empty();
+        self.items.splice(position..position, tracks);
+        if !was_empty && position <= self.position {
+            self.position += count;
+        }
+        Ok(())
+    }
+
+    /// Inserts `tracks` right after the current item.
+    pub fn insert_next(&mut self, tracks: Vec<MusicKitTrack>) {
+        let position = if self.items.is_empty() {
+            0
+        } else {
+            self.position + 1
+        };
+        self.items.splice(position..position, tracks);
+    }
+
+    /// Appends `tracks` to the end of the queue.
+    pub fn append(&mut self, tracks: Vec<MusicKitTrack>) {
+        self.items.extend(tracks);
+    }
+
+    /// Moves the item at `from` so that it ends up at index `to`.
+    ///
+    /// The cursor follows the current item, wherever it ends up.
+    pub fn move_item(&mut self, from: usize, to: usize) -> Result<()> {
+        self.move_items(&[from], to)
+    }
+
+    /// Moves the items at `indices` into one contiguous block starting at index `to`,
+    /// keeping their relative order.
+    ///
+    /// `to` is a position in the queue as it is once the moved items are taken out, so
+    /// it ranges up to the number of items left. The cursor follows the current item.
+    pub fn move_items(&mut self, indices: &[usize], to: usize) -> Result<()> {
+        let mut moved = indices.to_vec();
+        moved.sort_unstable();
+        moved.dedup();
+        if let Some(&index) = moved.iter().find(|&&index| index >= self.items.len()) {
+            return Err(Error::QueuePositionOutOfRange {
+                position: index,
+                len: self.items.len(),
+            });
+        }
+        let remaining = self.items.len() - moved.len();
+        if to > remaining {
+            return Err(Error::QueuePositionOutOfRange {
+                position: to,
+                len: remaining,
+            });
+        }
+
+        // Reorder original indices first, then rebuild the items and find the cursor.
+        let mut order: Vec<usize> = (0..self.items.len())
+            .filter(|index| moved.binary_search(index).is_err())
+            .collect();
+        order.splice(to..to, moved);
+        let mut items: Vec<Option<MusicKitTrack>> = std::mem::take(&mut self.items)
+            .into_iter()
+            .map(Some)
+            .collect();
+        self.items = order
+            .iter()
+            .filter_map(|&index| items[index].take())
+            .collect();
+        if let Some(position) = order.iter().position(|&index| index == self.position) {
+            self.position = position;
+        }
+        Ok(())
+    }
+
+    /// Removes the item at `index`.
+    ///
+    /// Removing the current item makes the following one current, or the new last
+    /// item when it was at the end.
+    pub fn remove_at(&mut self, index: usize) -> Result<MusicKitTrack> {
+        self.check_index(index)?;
+        let track = self.items.remove(index);
+        if index < self.position {
+            self.position -= 1;
+        } else {
+            self.position = self.position.min(self.items.len().saturating_sub(1));
+        }
+        Ok(track)
+    }
+
+    /// Removes the first item with `track_id`.
+    pub fn remove_track(&mut self, track_id: &str) -> Result<MusicKitTrack> {
+        let index = self
+            .index_of(track_id)
+            .ok_or_else(|| Error::TrackNotInQueue(track_id.to_string()))?;
+        self.remove_at(index)
+    }
+
+    /// Removes the items from `start` up to, but not including, `end`.
+    ///
+    /// When the current item is removed, the first item after the range becomes
+    /// current, or the new last item when the range reached the end.
+    pub fn remove_range(&mut self, start: usize, end: usize) -> Result<Vec<MusicKitTrack>> {
+        if end > self.items.len() {
+            return Err(Error::QueuePositionOutOfRange {
+                position: end,
+                len: self.items.len(),
+            });
+        }
+        if start > end {
+            return Err(Error::QueuePositionOutOfRange {
+                position: start,
+                len: end,
+            });
+        }
+        let removed: Vec<_> = self.items.drain(start..end).collect();
+        if self.position >= end {
+            self.position -= removed.len();
+        } else if self.position >= start {
+            self.position = start;
+        }
+        self.position = self.position.min(self.items.len().saturating_sub(1));
+        Ok(removed)
+    }
+
+    /// Removes every item.
+    pub fn clear(&mut self) {
+        self.items.clear();
+        self.position = 0;
+    }
+
+    /// Splits the queue into its tracks and cursor.
+    pub fn into_parts(self) -> (Vec<MusicKitTrack>, usize) {
+        (self.items, self.position)
+    }
+
+    pub fn to_response(&self) -> QueueResponse {
+        QueueResponse {
+            items: self.items.clone(),
+            position: self.position,
+        }
+    }
+
+    pub fn to_event(&self) -> QueueUpdateEvent {
+        QueueUpdateEvent {
+            items: self.items.clone(),
+            position: self.position,
+        }
+    }
+
+    fn check_index(&self, index: usize) -> Result<()> {
+        if index >= self.items.len() {
+            return Err(Error::QueuePositionOutOfRange {
+                position: index,
+                len: self.items.len(),
+            });
+        }
+        Ok(())
+    }
+}
+
+impl From<QueueResponse> for Queue {
+    fn from(response: QueueResponse) -> Self {
+        Self::with_position(response.items, response.position)
+    }
+}
+
+/// How a native player catches up with a queue change.
+///
+/// Handing a native player a whole new queue restarts the current item, so changes
+/// that keep it current are applied around it instead.
+#[derive(Debug, Clone, PartialEq)]
+pub enum QueueSync {
+    /// Clear the player, which cannot be handed an empty queue.
+    Clear,
+    /// Replace the items around the current one, which keeps playing undisturbed.
+    ///
+    /// `tracks[position]` is the item that was current before the change.
+    Rearrange {
+        tracks: Vec<MusicKitTrack>,
+        position: usize,
+    },
+    /// Replace the whole queue, starting at `position`.
+    Replace {
+        tracks: Vec<MusicKitTrack>,
+        position: usize,
+    },
+}
+
+impl QueueSync {
+    /// Plans how to bring a player showing `before` to `after`.
+    pub fn plan(before: &Queue, after: Queue) -> Self {
+        if after.is_empty() {
+            return QueueSync::Clear;
+        }
+        let current_kept = before
+            .current()
+            .zip(after.current())
+            .is_some_and(|(before, after)| before.id == after.id);
+        let (tracks, position) = after.into_parts();
+        if current_kept {
+            QueueSync::Rearrange { tracks, position }
+        } else {
+            QueueSync::Replace { tracks, position }
+        }
+    }
+}
+
+/// Bounded undo and redo stacks of queue snapshots.
+///
+/// Record the queue as it was before every change; [`QueueHistory::undo`] and
+/// [`QueueHistory::redo`] then trade the current queue for the snapshot to restore.
+#[derive(Debug, Clone)]
+pub struct QueueHistory {
+    undo: VecDeque<Queue>,
+    redo: Vec<Queue>,
+    limit: usize,
+}
+
+impl Default for QueueHistory {
+    fn default() -> Self {
+        Self::new(DEFAULT_HISTORY_LIMIT)
+    }
+}
+
+impl QueueHistory {
+    /// Creates a history keeping at most `limit` undo steps.
+    pub fn new(limit: usize) -> Self {
+        Self {
+            undo: VecDeque::new(),
+            redo: Vec::new(),
+            limit,
+        }
+    }
+
+    /// Records the queue as it was before a change, discarding the redo stack.
+    pub fn record(&mut self, before: Queue) {
+        self.push_undo(before);
+        self.redo.clear();
+    }
+
+    /// Returns the snapshot to restore, remembering `current` for [`QueueHistory::redo`].
+    pub fn undo(&mut self, current: Queue) -> Option<Queue> {
+        let previous = self.undo.pop_back()?;
+        self.redo.push(current);
+        Some(previous)
+    }
+
+    /// Returns the snapshot an undo replaced, remembering `current` for
+    /// [`QueueHistory::undo`].
+    pub fn redo(&mut self, current: Queue) -> Option<Queue> {
+        let next = self.redo.pop()?;
+        self.push_undo(current);
+        Some(next)
+    }
+
+    pub fn can_undo(&self) -> bool {
+        !self.undo.is_empty()
+    }
+
+    pub fn can_redo(&self) -> bool {
+        !self.redo.is_empty()
+    }
+
+    pub fn clear(&mut self) {
+        self.undo.clear();
+        self.redo.clear();
+    }
+
+    fn push_undo(&mut self, queue: Queue) {
+        if self.limit == 0 {
+            return;
+        }
+        if self.undo.len() == self.limit {
+            self.undo.pop_front();
+        }
+        self.undo.push_back(queue);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn track(id: &str) -> MusicKitTrack {
+        MusicKitTrack {
+            id: id.to_string(),
+            ..Default::default()
+        }
+    }
+
+    /// A queue of single-letter track IDs with the cursor on `position`.
+    fn queue(ids: &str, position: usize) -> Queue {
+        Queue::with_position(
+            ids.chars().map(|id| track(&id.to_string())).collect(),
+            position,
+        )
+    }
+
+    fn ids(queue: &Queue) -> String {
+        queue
+            .items()
+            .iter()
+            .map(|track| track.id.as_str())
+            .collect()
+    }
+
+    fn current(queue: &Queue) -> Option<&str> {
+        queue.current().map(|track| track.id.as_str())
+    }
+
+    #[test]
+    fn set_rejects_a_start_position_past_the_end() {
+        let mut queue = queue("abc", 1);
+        assert!(matches!(
+            queue.set(vec![track("x")], 1),
+            Err(Error::QueuePositionOutOfRange {
+                position: 1,
+                len: 1
+            })
+        ));
+        assert_eq!(ids(&queue), "abc");
+
+        queue.set(Vec::new(), 5).unwrap();
+        assert!(queue.is_empty());
+        assert_eq!(queue.position(), 0);
+    }
+
+    #[test]
+    fn update_keeps_the_current_item_or_rewinds() {
+        let mut queue = queue("abc", 1);
+        queue.update(vec![track("x"), track("y"), track("b")]);
+        assert_eq!((current(&queue), queue.position()), (Some("b"), 2));
+
+        queue.update(vec![track("x"), track("y")]);
+        assert_eq!((current(&queue), queue.position()), (Some("x"), 0));
+    }
+
+    #[test]
+    fn inserting_before_the_cursor_keeps_the_current_item() {
+        let mut queue = queue("abcd", 2);
+        queue.insert(1, vec![track("x"), track("y")]).unwrap();
+        assert_eq!(ids(&queue), "axybcd");
+        assert_eq!((current(&queue), queue.position()), (Some("c"), 4));
+
+        // Inserting at the cursor pushes the current item back as well.
+        queue.insert(4, vec![track("z")]).unwrap();
+        assert_eq!(ids(&queue), "axybzcd");
+        assert_eq!((current(&queue), queue.position()), (Some("c"), 5));
+    }
+
+    #[test]
+    fn inserting_after_the_cursor_leaves_it() {
+        let mut queue = queue("abcd", 1);
+        queue.insert(2, vec![track("x")]).unwrap();
+        queue.insert(5, vec![track("y")]).unwrap();
+        assert_eq!(ids(&queue), "abxcdy");
+        assert_eq!((current(&queue), queue.position()), (Some("b"), 1));
+
+        assert!(matches!(
+            queue.insert(7, vec![track("z")]),
+            Err(Error::QueuePositionOutOfRange {
+                position: 7,
+                len: 6
+            })
+        ));
+    }
+
+    #[test]
+    fn inserting_into_an_empty_queue_starts_at_the_first_item() {
+        let mut queue = Queue::new();
+        queue.insert(0, vec![track("a"), track("b")]).unwrap();
+        assert_eq!((current(&queue), queue.position()), (Some("a"), 0));
+
+        let mut queue = Queue::new();
+        queue.insert_next(vec![track("a"), track("b")]);
+        assert_eq!((current(&queue), queue.position()), (Some("a"), 0));
+    }
+
+    #[test]
+    fn insert_next_and_append_place_items_around_the_cursor() {
+        let mut queue = queue("abc", 1);
+        queue.insert_next(vec![track("x")]);
+        queue.append(vec![track("y")]);
+        assert_eq!(ids(&queue), "abxcy");
+        assert_eq!((current(&queue), queue.position()), (Some("b"), 1));
+    }
+
+    #[test]
+    fn removing_before_the_cursor_keeps_the_current_item() {
+        let mut queue = queue("abcd", 2);
+        assert_eq!(queue.remove_at(0).unwrap().id, "a");
+        assert_eq!((current(&queue), queue.position()), (Some("c"), 1));
+
+        assert_eq!(queue.remove_track("d").unwrap().id, "d");
+        assert_eq!((current(&queue), queue.position()), (Some("c"), 1));
+    }
+
+    #[test]
+    fn removing_the_current_item_moves_to_the_next_one() {
+        let mut queue = queue("abcd", 1);
+        queue.remove_at(1).unwrap();
+        assert_eq!(ids(&queue), "acd");
+        assert_eq!((current(&queue), queue.position()), (Some("c"), 1));
+
+        queue.remove_track("c").unwrap();
+        assert_eq!((current(&queue), queue.position()), (Some("d"), 1));
+    }
+
+    #[test]
+    fn removing_the_current_last_item_moves_back() {
+        let mut queue = queue("abc", 2);
+        queue.remove_at(2).unwrap();
+        assert_eq!((current(&queue), queue.position()), (Some("b"), 1));
+    }
+
+    #[test]
+    fn removing_the_only_item_empties_the_queue() {
+        let mut queue = queue("a", 0);
+        queue.remove_track("a").unwrap();
+        assert!(queue.is_empty());
+        assert_eq!((current(&queue), queue.position()), (None, 0));
+    }
+
+    #[test]
+    fn removing_a_missing_item_fails() {
+        let mut queue = queue("abc", 0);
+        assert!(matches!(
+            queue.remove_at(3),
+            Err(Error::QueuePositionOutOfRange {
+                position: 3,
+                len: 3
+            })
+        ));
+        assert!(matches!(
+            queue.remove_track("x"),
+            Err(Error::TrackNotInQueue(id)) if id == "x"
+        ));
+        assert_eq!(ids(&queue), "abc");
+    }
+
+    #[test]
+    fn removing_a_range_before_the_cursor_shifts_it_back() {
+        let mut queue = queue("abcdef", 4);
+        assert_eq!(queue.remove_range(1, 3).unwrap().len(), 2);
+        assert_eq!(ids(&queue), "adef");
+        assert_eq!((current(&queue), queue.position()), (Some("e"), 2));
+    }
+
+    #[test]
+    fn removing_a_range_after_the_cursor_leaves_it() {
+        let mut queue = queue("abcdef", 1);
+        queue.remove_range(3, 6).unwrap();
+        assert_eq!(ids(&queue), "abc");
+        assert_eq!((current(&queue), queue.position()), (Some("b"), 1));
+    }
+
+    #[test]
+    fn removing_a_range_with_the_current_item_moves_past_it() {
+        let mut middle = queue("abcdef", 2);
+        middle.remove_range(1, 4).unwrap();
+        assert_eq!(ids(&middle), "aef");
+        assert_eq!((current(&middle), middle.position()), (Some("e"), 1));
+
+        // With nothing after the range, the new last item becomes current.
+        let mut at_end = queue("abcdef", 4);
+        at_end.remove_range(3, 6).unwrap();
+        assert_eq!((current(&at_end), at_end.position()), (Some("c"), 2));
+    }
+
+    #[test]
+    fn removing_the_whole_range_empties_the_queue() {
+        let mut queue = queue("abc", 1);
+        queue.remove_range(0, 3).unwrap();
+        assert!(queue.is_empty());
+        assert_eq!(queue.position(), 0);
+    }
+
+    #[test]
+    fn removing_an_empty_range_changes_nothing() {
+        let mut queue = queue("abc", 1);
+        assert!(queue.remove_range(1, 1).unwrap().is_empty());
+        assert!(queue.remove_range(3, 3).unwrap().is_empty());
+        assert_eq!(ids(&queue), "abc");
+        assert_eq!(queue.position(), 1);
+    }
+
+    #[test]
+    fn removing_an_out_of_range_range_fails() {
+        let mut queue = queue("abc", 1);
+        assert!(matches!(
+            queue.remove_range(1, 4),
+            Err(Error::QueuePositionOutOfRange {
+                position: 4,
+                len: 3
+            })
+        ));
+        assert!(matches!(
+            queue.remove_range(2, 1),
+            Err(Error::QueuePositionOutOfRange {
+                position: 2,
+                len: 1
+            })
+        ));
+        assert_eq!(ids(&queue), "abc");
+        assert_eq!(queue.position(), 1);
+    }
+
+    #[test]
+    fn moving_an_item_carries_the_cursor_with_it() {
+        let mut queue = queue("abcde", 1);
+        queue.move_item(1, 3).unwrap();
+        assert_eq!(ids(&queue), "acdbe");
+        assert_eq!((current(&queue), queue.position()), (Some("b"), 3));
+    }
+
+    #[test]
+    fn moving_other_items_across_the_cursor_keeps_the_current_item() {
+        let mut queue = queue("abcde", 2);
+        queue.move_item(0, 4).unwrap();
+        assert_eq!(ids(&queue), "bcdea");
+        assert_eq!((current(&queue), queue.position()), (Some("c"), 1));
+
+        queue.move_item(4, 0).unwrap();
+        assert_eq!(ids(&queue), "abcde");
+        assert_eq!((current(&queue), queue.position()), (Some("c"), 2));
+    }
+
+    #[test]
+    fn moved_items_form_one_block_in_their_original_order() {
+        let mut queue = queue("abcdef", 0);
+        queue.move_items(&[4, 1, 4], 2).unwrap();
+        assert_eq!(ids(&queue), "acbedf");
+    }
+
+    #[test]
+    fn moving_items_to_a_position_inside_the_selection() {
+        // `to` counts positions once the selection is taken out, so moving `b` and `c`
+        // to 1 puts them back where they were.
+        let mut queue = queue("abcde", 2);
+        queue.move_items(&[1, 2], 1).unwrap();
+        assert_eq!(ids(&queue), "abcde");
+        assert_eq!((current(&queue), queue.position()), (Some("c"), 2));
+
+        // A gapped selection closes up at the position of its first item.
+        queue.move_items(&[1, 3], 1).unwrap();
+        assert_eq!(ids(&queue), "abdce");
+        assert_eq!((current(&queue), queue.position()), (Some("c"), 3));
+    }
+
+    #[test]
+    fn moving_items_out_of_range_fails() {
+        let mut queue = queue("abcde", 2);
+        assert!(matches!(
+            queue.move_items(&[1, 5], 0),
+            Err(Error::QueuePositionOutOfRange {
+                position: 5,
+                len: 5
+            })
+        ));
+        assert!(matches!(
+            queue.move_items(&[1, 2], 4),
+            Err(Error::QueuePositionOutOfRange {
+                position: 4,
+                len: 3
+            })
+        ));
+        assert_eq!(ids(&queue), "abcde");
+    }
+
+    /// Plans `operation` against `before`, checking it succeeds.
+    fn plan(before: &Queue, operation: impl FnOnce(&mut Queue) -> Result<()>) -> QueueSync {
+        let mut after = before.clone();
+        operation(&mut after).unwrap();
+        QueueSync::plan(before, after)
+    }
+
+    /// The IDs and cursor a [`QueueSync::Rearrange`] hands to the player.
+    fn rearranged(sync: QueueSync) -> (String, usize) {
+        match sync {
+            QueueSync::Rearrange { tracks, position } => (
+                tracks.iter().map(|track| track.id.as_str()).collect(),
+                position,
+            ),
+            other => panic!("expected a rearrangement, got {other:?}"),
+        }
+    }
+
+    #[test]
+    fn moving_the_current_item_rearranges_around_it() {
+        let before = queue("abcde", 1);
+        let (ids, position) = rearranged(plan(&before, |queue| queue.move_item(1, 3)));
+        assert_eq!((ids.as_str(), position), ("acdbe", 3));
+        assert_eq!(&ids[position..=position], "b");
+    }
+
+    #[test]
+    fn moving_other_items_rearranges_around_the_current_one() {
+        let before = queue("abcde", 2);
+        let (ids, position) = rearranged(plan(&before, |queue| queue.move_items(&[0, 4], 1)));
+        assert_eq!((ids.as_str(), position), ("baecd", 3));
+        assert_eq!(&ids[position..=position], "c");
+
+        let (ids, position) = rearranged(plan(&before, |queue| queue.move_items(&[3], 0)));
+        assert_eq!((ids.as_str(), position), ("dabce", 3));
+    }
+
+    #[test]
+    fn edits_around_the_current_item_rearrange() {
+        let before = queue("abc", 1);
+        let sync = plan(&before, |queue| queue.insert(0, vec![track("x")]));
+        assert_eq!(rearranged(sync), ("xabc".to_string(), 2));
+        let sync = plan(&before, |queue| queue.remove_at(2).map(drop));
+        assert_eq!(rearranged(sync), ("ab".to_string(), 1));
+    }
+
+    #[test]
+    fn losing_the_current_item_replaces_the_queue() {
+        let before = queue("abc", 1);
+        assert_eq!(
+            plan(&before, |queue| queue.remove_at(1).map(drop)),
+            QueueSync::Replace {
+                tracks: vec![track("a"), track("c")],
+                position: 1,
+            }
+        );
+        assert!(matches!(
+            plan(&Queue::new(), |queue| {
+                queue.append(vec![track("a")]);
+                Ok(())
+            }),
+            QueueSync::Replace { position: 0, .. }
+        ));
+    }
+
+    #[test]
+    fn removing_the_last_item_clears_the_player() {
+        let before = queue("a", 0);
+        assert_eq!(
+            plan(&before, |queue| queue.remove_at(0).map(drop)),
+            QueueSync::Clear
+        );
+        assert_eq!(
+            plan(&before, |queue| queue.remove_track("a").map(drop)),
+            QueueSync::Clear
+        );
+    }
+
+    #[test]
+    fn removing_every_item_in_a_range_clears_the_player() {
+        let before = queue("abc", 1);
+        assert_eq!(
+            plan(&before, |queue| queue.remove_range(0, 3).map(drop)),
+            QueueSync::Clear
+        );
+    }
+
+    #[test]
+    fn updating_to_no_tracks_clears_the_player() {
+        let before = queue("abc", 1);
+        assert_eq!(
+            plan(&before, |queue| {
+                queue.update(Vec::new());
+                Ok(())
+            }),
+            QueueSync::Clear
+        );
+    }
+
+    #[test]
+    fn undo_and_redo_trade_snapshots() {
+        let mut history = QueueHistory::default();
+        assert!(!history.can_undo());
+        history.record(queue("a", 0));
+
+        let restored = history.undo(queue("ab", 1)).unwrap();
+        assert_eq!(ids(&restored), "a");
+        assert!(!history.can_undo());
+        assert!(history.can_redo());
+
+        let redone = history.redo(restored).unwrap();
+        assert_eq!((ids(&redone), redone.position()), ("ab".to_string(), 1));
+        assert!(history.can_undo());
+        assert!(!history.can_redo());
+        assert!(history.redo(redone).is_none());
+    }
+
+    #[test]
+    fn history_drops_the_oldest_snapshot_past_the_limit() {
+        let mut history = QueueHistory::default();
+        for length in 0..=DEFAULT_HISTORY_LIMIT {
+            history.record(queue(&"a".repeat(length), 0));
+        }
+
+        let mut current = Queue::new();
+        let mut undone = 0;
+        while history.can_undo() {
+            current = history.undo(current).unwrap();
+            undone += 1;
+        }
+        assert_eq!(undone, DEFAULT_HISTORY_LIMIT);
+        // The empty snapshot recorded first was dropped.
+        assert_eq!(current.len(), 1);
+    }
+
+    #[test]
+    fn a_new_change_clears_redo() {
+        let mut history = QueueHistory::default();
+        history.record(queue("a", 0));
+        history.record(queue("ab", 0));
+        let restored = history.undo(queue("abc", 0)).unwrap();
+        assert!(history.can_redo());
+
+        history.record(restored);
+        assert!(!history.can_redo());
+        assert!(history.redo(queue("abx", 0)).is_none());
+        assert_eq!(ids(&history.undo(queue("abx", 0)).unwrap()), "ab");
+    }
+
+    #[test]
+    fn a_zero_limit_keeps_no_history() {
+        let mut history = QueueHistory::new(0);
+        history.record(queue("a", 0));
+        assert!(!history.can_undo());
+    }
+}
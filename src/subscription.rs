@@ -0,0 +1,192 @@
+//! Observing player events from Rust.
+//!
+//! Every `PLAYER_ADAPTER_EVENTS_*` event the app receives is parsed into a
+//! [`PlayerEvent`] and broadcast to every [`PlayerEventStream`] returned by
+//! [`crate::MusicKit::subscribe`]. Each stream buffers events independently, so a slow
+//! consumer only loses its own events and is told how many with
+//! [`PlayerEvent::Lagged`].
+
+use std::{
+    fmt,
+    future::Future,
+    pin::Pin,
+    task::{ready, Context, Poll},
+};
+
+use futures_core::Stream;
+use tokio::sync::broadcast::{self, error::RecvError};
+
+use crate::{events::*, models::*};
+
+/// How many events a subscriber may fall behind before it starts missing them.
+pub const DEFAULT_EVENT_CAPACITY: usize = 256;
+
+/// The events [`crate::MusicKit::subscribe`] delivers.
+pub(crate) const SUBSCRIBED_EVENTS: [&str; 5] = [
+    PLAYER_ADAPTER_EVENTS_STATE_UPDATE,
+    PLAYER_ADAPTER_EVENTS_TRACK_CHANGE,
+    PLAYER_ADAPTER_EVENTS_QUEUE_UPDATE,
+    PLAYER_ADAPTER_EVENTS_AUTHORIZATION_STATUS_CHANGE,
+    PLAYER_ADAPTER_EVENTS_ERROR,
+];
+
+/// A player event, with the payload of the matching `PLAYER_ADAPTER_EVENTS_*` event.
+#[derive(Debug, Clone)]
+#[non_exhaustive]
+pub enum PlayerEvent {
+    StateUpdate(StateUpdateEvent),
+    TrackChange(TrackChangeEvent),
+    QueueUpdate(QueueUpdateEvent),
+    AuthorizationStatusChange(AuthorizationStatusResponse),
+    Error(ErrorEvent),
+    /// The subscriber fell behind and missed `skipped` events.
+    Lagged {
+        skipped: u64,
+    },
+}
+
+impl PlayerEvent {
+    /// Parses an emitted event, or returns `None` for events without a variant.
+    pub(crate) fn parse(event: &str, payload: &str) -> Option<serde_json::Result<Self>> {
+        let parsed = match event {
+            PLAYER_ADAPTER_EVENTS_STATE_UPDATE => {
+                serde_json::from_str(payload).map(Self::StateUpdate)
+            }
+            PLAYER_ADAPTER_EVENTS_TRACK_CHANGE => {
+                serde_json::from_str(payload).map(Self::TrackChange)
+            }
+            PLAYER_ADAPTER_EVENTS_QUEUE_UPDATE => {
+                serde_json::from_str(payload).map(Self::QueueUpdate)
+            }
+            PLAYER_ADAPTER_EVENTS_AUTHORIZATION_STATUS_CHANGE => {
+                serde_json::from_str(payload).map(Self::AuthorizationStatusChange)
+            }
+            PLAYER_ADAPTER_EVENTS_ERROR => serde_json::from_str(payload).map(Self::Error),
+            _ => return None,
+        };
+        Some(parsed)
+    }
+}
+
+type Recv = Pin<Box<dyn Future<Output = (Result<PlayerEvent, RecvError>, Receiver)> + Send>>;
+type Receiver = broadcast::Receiver<PlayerEvent>;
+
+/// A [`Stream`] of the player events emitted after it was created.
+pub struct PlayerEventStream {
+    recv: Recv,
+}
+
+impl PlayerEventStream {
+    pub(crate) fn new(receiver: Receiver) -> Self {
+        Self {
+            recv: Box::pin(recv(receiver)),
+        }
+    }
+}
+
+async fn recv(mut receiver: Receiver) -> (Result<PlayerEvent, RecvError>, Receiver) {
+    let result = receiver.recv().await;
+    (result, receiver)
+}
+
+impl Stream for PlayerEventStream {
+    type Item = PlayerEvent;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<PlayerEvent>> {
+        let (result, receiver) = ready!(self.recv.as_mut().poll(cx));
+        self.recv = Box::pin(recv(receiver));
+        Poll::Ready(match result {
+            Ok(event) => Some(event),
+            Err(RecvError::Lagged(skipped)) => Some(PlayerEvent::Lagged { skipped }),
+            Err(RecvError::Closed) => None,
+        })
+    }
+}
+
+impl fmt::Debug for PlayerEventStream {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("PlayerEventStream").finish_non_exhaustive()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::future::poll_fn;
+
+    use super::*;
+
+    async fn next(stream: &mut PlayerEventStream) -> Option<PlayerEvent> {
+        poll_fn(|cx| Pin::new(&mut *stream).poll_next(cx)).await
+    }
+
+    fn queue_update(position: usize) -> PlayerEvent {
+        PlayerEvent::QueueUpdate(QueueUpdateEvent {
+            items: Vec::new(),
+            position,
+        })
+    }
+
+    fn position(event: Option<PlayerEvent>) -> usize {
+        match event {
+            Some(PlayerEvent::QueueUpdate(update)) => update.position,
+            other => panic!("expected a queue update, got {other:?}"),
+        }
+    }
+
+    #[tokio::test]
+    async fn every_subscriber_receives_every_event() {
+        let (sender, _) = broadcast::channel(DEFAULT_EVENT_CAPACITY);
+        let mut first = PlayerEventStream::new(sender.subscribe());
+        let mut second = PlayerEventStream::new(sender.subscribe());
+
+        sender.send(queue_update(1)).unwrap();
+        sender.send(queue_update(2)).unwrap();
+        drop(sender);
+
+        for stream in [&mut first, &mut second] {
+            assert_eq!(position(next(stream).await), 1);
+            assert_eq!(position(next(stream).await), 2);
+            assert!(next(stream).await.is_none());
+        }
+    }
+
+    #[tokio::test]
+    async fn a_slow_subscriber_is_told_how_many_events_it_missed() {
+        let (sender, _) = broadcast::channel(2);
+        let mut slow = PlayerEventStream::new(sender.subscribe());
+        let mut fast = PlayerEventStream::new(sender.subscribe());
+
+        sender.send(queue_update(0)).unwrap();
+        assert_eq!(position(next(&mut fast).await), 0);
+        for position in 1..5 {
+            sender.send(queue_update(position)).unwrap();
+        }
+
+        assert!(matches!(
+            next(&mut slow).await,
+            Some(PlayerEvent::Lagged { skipped: 3 })
+        ));
+        assert_eq!(position(next(&mut slow).await), 3);
+        assert_eq!(position(next(&mut slow).await), 4);
+
+        assert!(matches!(
+            next(&mut fast).await,
+            Some(PlayerEvent::Lagged { skipped: 2 })
+        ));
+        assert_eq!(position(next(&mut fast).await), 3);
+    }
+
+    #[test]
+    fn only_subscribed_events_are_parsed() {
+        let parsed = PlayerEvent::parse(
+            PLAYER_ADAPTER_EVENTS_QUEUE_UPDATE,
+            r#"{"items":[],"position":3}"#,
+        );
+        assert_eq!(position(Some(parsed.unwrap().unwrap())), 3);
+
+        let parsed = PlayerEvent::parse(PLAYER_ADAPTER_EVENTS_ERROR, r#"{"items":[]}"#);
+        assert!(parsed.unwrap().is_err());
+
+        assert!(PlayerEvent::parse("unknown-event", "{}").is_none());
+    }
+}
@@ -0,0 +1,169 @@
+//! Coalescing bursts of state updates.
+
+use std::{
+    sync::{Mutex, PoisonError},
+    time::{Duration, Instant},
+};
+
+use crate::models::StateUpdateEvent;
+
+/// Limits state updates to one per interval without losing the latest one.
+///
+/// The first update after a quiet interval goes out immediately. Updates arriving
+/// within the interval replace each other, and the last of them is flushed once the
+/// interval has passed.
+#[derive(Debug)]
+pub(crate) struct StateThrottle {
+    interval: Duration,
+    inner: Mutex<ThrottleState>,
+}
+
+#[derive(Debug, Default)]
+struct ThrottleState {
+    last_emit: Option<Instant>,
+    pending: Option<StateUpdateEvent>,
+    flush_scheduled: bool,
+}
+
+/// What to do with an update offered to a [`StateThrottle`].
+#[derive(Debug)]
+pub(crate) enum Throttled {
+    /// Emit the update now.
+    Emit(Box<StateUpdateEvent>),
+    /// The update is held back; call [`StateThrottle::flush`] after the delay.
+    FlushAfter(Duration),
+    /// The update replaced one that is already waiting for a flush.
+    Coalesced,
+}
+
+impl StateThrottle {
+    pub(crate) fn new(interval: Duration) -> Self {
+        Self {
+            interval,
+            inner: Default::default(),
+        }
+    }
+
+    pub(crate) fn offer(&self, state: StateUpdateEvent, now: Instant) -> Throttled {
+        let mut inner = self.inner.lock().unwrap_or_else(PoisonError::into_inner);
+        let elapsed = inner
+            .last_emit
+            .map(|last| now.saturating_duration_since(last));
+        if inner.flush_scheduled {
+            inner.pending = Some(state);
+            return Throttled::Coalesced;
+        }
+        match elapsed {
+            Some(elapsed) if elapsed < self.interval => {
+                inner.pending = Some(state);
+                inner.flush_scheduled = true;
+                Throttled::FlushAfter(self.interval - elapsed)
+            }
+            _ => {
+                inner.last_emit = Some(now);
+                Throttled::Emit(Box::new(state))
+            }
+        }
+    }
+
+    /// Takes the update held back since the last emit, if any.
+    pub(crate) fn flush(&self, now: Instant) -> Option<StateUpdateEvent> {
+        let mut inner = self.inner.lock().unwrap_or_else(PoisonError::into_inner);
+        inner.flush_scheduled = false;
+        let pending = inner.pending.take()?;
+        inner.last_emit = Some(now);
+        Some(pending)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::{RepeatMode, ShuffleMode};
+
+    const INTERVAL: Duration = Duration::from_millis(100);
+
+    /// A state update told apart by its `current_time`.
+    fn state(current_time: f64) -> StateUpdateEvent {
+        StateUpdateEvent {
+            playing: true,
+            paused: false,
+            current_track: None,
+            current_time,
+            duration: 200.0,
+            progress: 0.0,
+            queue_position: 0,
+            shuffle_mode: ShuffleMode::Off,
+            repeat_mode: RepeatMode::None,
+            volume: 1.0,
+        }
+    }
+
+    fn ms(ms: u64) -> Duration {
+        Duration::from_millis(ms)
+    }
+
+    fn emitted(throttled: Throttled) -> f64 {
+        match throttled {
+            Throttled::Emit(state) => state.current_time,
+            other => panic!("expected an emit, got {other:?}"),
+        }
+    }
+
+    #[test]
+    fn the_first_update_goes_out_immediately() {
+        let throttle = StateThrottle::new(INTERVAL);
+        let start = Instant::now();
+        assert_eq!(emitted(throttle.offer(state(1.0), start)), 1.0);
+        assert!(throttle.flush(start).is_none());
+    }
+
+    #[test]
+    fn updates_within_the_interval_collapse_into_the_latest() {
+        let throttle = StateThrottle::new(INTERVAL);
+        let start = Instant::now();
+        emitted(throttle.offer(state(1.0), start));
+
+        assert!(matches!(
+            throttle.offer(state(2.0), start + ms(30)),
+            Throttled::FlushAfter(delay) if delay == ms(70)
+        ));
+        assert!(matches!(
+            throttle.offer(state(3.0), start + ms(60)),
+            Throttled::Coalesced
+        ));
+        assert!(matches!(
+            throttle.offer(state(4.0), start + ms(90)),
+            Throttled::Coalesced
+        ));
+
+        let flushed = throttle.flush(start + ms(100)).unwrap();
+        assert_eq!(flushed.current_time, 4.0);
+        assert!(throttle.flush(start + ms(100)).is_none());
+    }
+
+    #[test]
+    fn the_interval_restarts_at_each_flush() {
+        let throttle = StateThrottle::new(INTERVAL);
+        let start = Instant::now();
+        emitted(throttle.offer(state(1.0), start));
+        throttle.offer(state(2.0), start + ms(50));
+        throttle.flush(start + ms(100)).unwrap();
+
+        assert!(matches!(
+            throttle.offer(state(3.0), start + ms(150)),
+            Throttled::FlushAfter(delay) if delay == ms(50)
+        ));
+        throttle.flush(start + ms(200)).unwrap();
+        assert_eq!(emitted(throttle.offer(state(4.0), start + ms(300))), 4.0);
+    }
+
+    #[test]
+    fn an_update_after_a_quiet_interval_goes_out_immediately() {
+        let throttle = StateThrottle::new(INTERVAL);
+        let start = Instant::now();
+        emitted(throttle.offer(state(1.0), start));
+        assert_eq!(emitted(throttle.offer(state(2.0), start + ms(100))), 2.0);
+        assert_eq!(emitted(throttle.offer(state(3.0), start + ms(250))), 3.0);
+    }
+}